@@ -0,0 +1,65 @@
+//! Measures [`BInfoMatcher`] against a synthetic corpus of builds.
+//!
+//! Run with `cargo bench --bench search`.
+
+use std::hint::black_box;
+
+use blrs::{
+    info::VerboseVersion,
+    search::{BInfoMatcher, VersionSearchQuery},
+    BasicBuildInfo,
+};
+use chrono::DateTime;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const CORPUS_SIZE: usize = 5000;
+
+const REPOS: [&str; 3] = ["daily", "experimental", "patch"];
+const BRANCHES: [&str; 4] = ["main", "v42", "cycles-light-linking", "PR123456"];
+
+fn corpus() -> Vec<(BasicBuildInfo, String)> {
+    (0..CORPUS_SIZE)
+        .map(|i| {
+            let hash = format!["{:012x}", (i as u64).wrapping_mul(0x9e3779b97f4a7c15) >> 16];
+            let basic = BasicBuildInfo {
+                ver: VerboseVersion::new(
+                    2 + (i % 3) as u64,
+                    (i % 10) as u64,
+                    (i % 21) as u64,
+                    Some(["stable", "alpha", "beta"][i % 3]),
+                    Some(BRANCHES[i % BRANCHES.len()]),
+                    Some(&hash),
+                ),
+                commit_dt: DateTime::from_timestamp(1_700_000_000 + (i as i64 * 3_600), 0).unwrap(),
+            };
+            (basic, REPOS[i % REPOS.len()].to_string())
+        })
+        .collect()
+}
+
+fn search(c: &mut Criterion) {
+    let builds = corpus();
+    let matcher = BInfoMatcher::new(&builds);
+    let mut group = c.benchmark_group("search");
+
+    for q in [
+        "*.*.*",
+        "^.^.^",
+        "4.^.^-main@^",
+        "experimental/3.-.*",
+        "*.*.*-PR123456",
+    ] {
+        let query = VersionSearchQuery::try_from(q).unwrap();
+        group.bench_with_input(BenchmarkId::new("find_indices", q), &query, |b, query| {
+            b.iter(|| matcher.find_indices(black_box(query)).len())
+        });
+        group.bench_with_input(BenchmarkId::new("find_all", q), &query, |b, query| {
+            b.iter(|| matcher.find_all(black_box(query)).len())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group![benches, search];
+criterion_main![benches];
@@ -0,0 +1,42 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+/// Writes `data` to `path` without readers ever observing a half-written file.
+///
+/// The data is written to a uniquely named temporary file next to `path`, which is then renamed over it.
+/// Renames within a directory are atomic, so concurrent writers can't interleave their data;
+/// the last writer to finish wins.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path has no file name"))?;
+
+    let tmp = path.with_file_name(format![
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        uuid::Uuid::new_v4()
+    ]);
+
+    let result = File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+
+    result
+}
+
+/// There are no other processes to race against on wasm, so the file is written directly.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    File::create(path)?.write_all(data)
+}
@@ -1,3 +1,5 @@
 mod extensions;
+mod formats;
 
 pub use extensions::*;
+pub use formats::BuildArtifactFormat;
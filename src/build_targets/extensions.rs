@@ -1,5 +1,12 @@
 use std::env::consts::{ARCH, OS};
 
+use serde::{Deserialize, Serialize};
+
+use crate::fetching::build_schemas::BlenderBuildSchema;
+
+use super::BuildArtifactFormat;
+
+#[cfg(not(target_arch = "wasm32"))]
 use crate::repos::{BuildEntry, RepoEntry};
 
 /// File extension typically used for Linux targets.
@@ -12,6 +19,72 @@ pub const TARGET_MACOS_EXT: &str = "dmg";
 /// Readable file types corresponding to different target operating systems.
 pub const READABLE_FILETYPES: [&str; 3] = [TARGET_LINUX_EXT, TARGET_WINDOWS_EXT, TARGET_MACOS_EXT];
 
+/// The extension used for builds of `platform` when no other one is preferred, from [`READABLE_FILETYPES`].
+///
+/// ```
+/// use blrs::build_targets::default_extension;
+///
+/// assert_eq![default_extension("linux"), Some("xz")];
+/// assert_eq![default_extension("darwin"), Some("dmg")];
+/// assert_eq![default_extension("haiku"), None];
+/// ```
+pub fn default_extension(platform: &str) -> Option<&'static str> {
+    match platform {
+        "linux" => Some(TARGET_LINUX_EXT),
+        "windows" => Some(TARGET_WINDOWS_EXT),
+        "darwin" | "macos" => Some(TARGET_MACOS_EXT),
+        _ => None,
+    }
+}
+
+/// Checks whether two extensions refer to the same format, so that `xz` matches `tar.xz`.
+pub(crate) fn extensions_match(a: &str, b: &str) -> bool {
+    a == b
+        || BuildArtifactFormat::from_extension(a)
+            .is_some_and(|f| Some(f) == BuildArtifactFormat::from_extension(b))
+}
+
+/// Checks whether a file extension belongs to a Windows installer rather than an archive.
+///
+/// ```
+/// use blrs::build_targets::is_installer_extension;
+///
+/// assert![is_installer_extension("msi")];
+/// assert![!is_installer_extension("zip")];
+/// ```
+pub fn is_installer_extension(extension: &str) -> bool {
+    BuildArtifactFormat::from_extension(extension).is_some_and(|f| f.is_installer())
+}
+
+/// Returns the platform, as named by the builder, that builds with this extension are made for.
+///
+/// ```
+/// use blrs::build_targets::extension_platform;
+///
+/// assert_eq![extension_platform("tar.xz"), Some("linux")];
+/// assert_eq![extension_platform("msi"), Some("windows")];
+/// assert_eq![extension_platform("sha256"), None];
+/// ```
+pub fn extension_platform(extension: &str) -> Option<&'static str> {
+    BuildArtifactFormat::from_extension(extension).and_then(|f| f.platform())
+}
+
+/// Checks whether a file extension belongs to an actual build, rather than a sidecar file like a `.sha256` checksum.
+///
+/// Shortened extensions are accepted, so `xz` is treated like `tar.xz`.
+///
+/// ```
+/// use blrs::build_targets::is_build_extension;
+///
+/// assert![is_build_extension("tar.xz")];
+/// assert![is_build_extension("msi")];
+/// assert![!is_build_extension("sha256")];
+/// assert![!is_build_extension("msix")];
+/// ```
+pub fn is_build_extension(extension: &str) -> bool {
+    BuildArtifactFormat::from_extension(extension).is_some_and(|f| f.is_installable())
+}
+
 /// Retrieves the appropriate target setup based on the current system architecture and operating system.
 ///
 /// If the platform is not supported, returns `None`.
@@ -30,10 +103,120 @@ pub fn get_target_setup() -> Option<(&'static str, &'static str, &'static str)>
     }
 }
 
+/// Maps the names a platform goes by to the one the builder uses: `linux`, `windows`, or `darwin`.
+/// Unknown platforms return `None`.
+///
+/// ```
+/// use blrs::build_targets::normalize_platform;
+///
+/// assert_eq![normalize_platform("macos"), Some("darwin")];
+/// assert_eq![normalize_platform("Windows"), Some("windows")];
+/// assert_eq![normalize_platform("haiku"), None];
+/// ```
+pub fn normalize_platform(platform: &str) -> Option<&'static str> {
+    match platform.trim().to_ascii_lowercase().as_str() {
+        "linux" => Some("linux"),
+        "windows" | "win" | "win32" | "win64" => Some("windows"),
+        "darwin" | "macos" | "mac" | "osx" => Some("darwin"),
+        _ => None,
+    }
+}
+
+/// Maps the names an architecture goes by to one name: `x86_64`, `arm64`, or `i686`.
+/// Blender's file names use `x64`, the builder uses `x86_64` and `amd64`, and Rust uses `aarch64`.
+/// Unknown architectures return `None`.
+///
+/// ```
+/// use blrs::build_targets::normalize_architecture;
+///
+/// assert_eq![normalize_architecture("amd64"), Some("x86_64")];
+/// assert_eq![normalize_architecture("x64"), Some("x86_64")];
+/// assert_eq![normalize_architecture("aarch64"), Some("arm64")];
+/// ```
+pub fn normalize_architecture(architecture: &str) -> Option<&'static str> {
+    match architecture.trim().to_ascii_lowercase().as_str() {
+        "x86_64" | "x64" | "amd64" | "x86-64" => Some("x86_64"),
+        "arm64" | "aarch64" => Some("arm64"),
+        "i686" | "i386" | "x86" => Some("i686"),
+        _ => None,
+    }
+}
+
+/// Checks whether two platforms are the same, after normalizing them with [`normalize_platform`].
+pub(crate) fn platforms_match(a: &str, b: &str) -> bool {
+    a == b || normalize_platform(a).is_some_and(|p| Some(p) == normalize_platform(b))
+}
+
+/// Checks whether two architectures are the same, after normalizing them with [`normalize_architecture`].
+pub(crate) fn architectures_match(a: &str, b: &str) -> bool {
+    a == b || normalize_architecture(a).is_some_and(|p| Some(p) == normalize_architecture(b))
+}
+
+/// Checks whether a build schema is for a `(platform, architecture, extension)` target, like the one
+/// returned by [`get_target_setup`].
+///
+/// The crate, the builder, and Blender's file names spell platforms and architectures differently
+/// (`amd64`, `x86_64`, and `x64`), so both sides are normalized with [`normalize_platform`] and
+/// [`normalize_architecture`] before being compared. Extensions are compared so that `xz` matches `tar.xz`.
+pub fn target_matches_schema(target: (&str, &str, &str), schema: &BlenderBuildSchema) -> bool {
+    platforms_match(target.0, &schema.platform)
+        && architectures_match(target.1, &schema.architecture)
+        && extensions_match(target.2, &schema.file_extension)
+}
+
+/// A platform and architecture combination that builds can be made for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BuildTarget {
+    /// The platform, as named by the builder (e.g. "linux", "windows", "darwin").
+    pub platform: String,
+    /// The architecture, as named by the builder (e.g. "x86_64", "arm64"). `None` matches any architecture.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub architecture: Option<String>,
+}
+
+impl BuildTarget {
+    /// Returns the target of the current system, if it is supported.
+    pub fn current() -> Option<Self> {
+        get_target_setup().map(|(platform, architecture, _)| Self {
+            platform: platform.to_string(),
+            architecture: Some(architecture.to_string()),
+        })
+    }
+
+    /// Checks whether a build schema was built for this target, see [`target_matches_schema`] for how names are compared.
+    pub fn matches(&self, schema: &BlenderBuildSchema) -> bool {
+        platforms_match(&self.platform, &schema.platform)
+            && self
+                .architecture
+                .as_ref()
+                .is_none_or(|a| architectures_match(a, &schema.architecture))
+    }
+}
+
+/// Keeps only the schemas built for one of `targets`, see [`BuildTarget::matches`].
+/// `None` keeps every schema.
+///
+/// [`crate::fetching::refresh::refresh_repo`] uses this to apply `BLRSConfig::fetch_targets`
+/// before writing a repo cache.
+pub fn filter_schemas_by_targets(
+    schemas: Vec<BlenderBuildSchema>,
+    targets: Option<&[BuildTarget]>,
+) -> Vec<BlenderBuildSchema> {
+    match targets {
+        None => schemas,
+        Some(targets) => schemas
+            .into_iter()
+            .filter(|s| targets.iter().any(|t| t.matches(s)))
+            .collect(),
+    }
+}
+
 /// Filters a list of repositories based on the target platform.
 ///
 /// This function iterates over each repository and filters the build entries within it.
 /// Build entries that don't match the target platform are removed.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
 pub fn filter_repos_by_target<V>(
     v: V,
     target: Option<(&'static str, &'static str, &'static str)>,
@@ -72,3 +255,89 @@ where
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::fetching::build_schemas::BlenderBuildSchema;
+
+    use super::{filter_schemas_by_targets, target_matches_schema, BuildTarget};
+
+    fn schema(platform: &str, architecture: &str, extension: &str) -> BlenderBuildSchema {
+        serde_json::from_value(serde_json::json!({
+            "app": "Blender",
+            "url": "https://builder.blender.org/download/daily/blender.zip",
+            "version": "4.3.0",
+            "branch": "main",
+            "patch": null,
+            "hash": "ddc9f92777cd",
+            "platform": platform,
+            "architecture": architecture,
+            "file_mtime": 1701263519,
+            "file_name": "blender",
+            "file_size": 1,
+            "file_extension": extension,
+            "release_cycle": "alpha"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_target_matches_schema() {
+        let linux = schema("linux", "x86_64", "tar.xz");
+        let windows = schema("windows", "amd64", "zip");
+        let windows_arm = schema("windows", "arm64", "zip");
+        let macos = schema("darwin", "arm64", "dmg");
+        let macos_intel = schema("darwin", "x86_64", "dmg");
+
+        assert![target_matches_schema(("linux", "x86_64", "xz"), &linux)];
+        assert![target_matches_schema(("linux", "x64", "tar.xz"), &linux)];
+        assert![!target_matches_schema(("linux", "arm64", "xz"), &linux)];
+        assert![target_matches_schema(("windows", "amd64", "zip"), &windows)];
+        assert![target_matches_schema(
+            ("windows", "x86_64", "zip"),
+            &windows
+        )];
+        assert![target_matches_schema(("windows", "x64", "zip"), &windows)];
+        assert![!target_matches_schema(
+            ("windows", "x64", "zip"),
+            &windows_arm
+        )];
+        assert![target_matches_schema(
+            ("windows", "aarch64", "zip"),
+            &windows_arm
+        )];
+        assert![target_matches_schema(("darwin", "arm64", "dmg"), &macos)];
+        assert![target_matches_schema(("macos", "aarch64", "dmg"), &macos)];
+        assert![!target_matches_schema(
+            ("darwin", "arm64", "dmg"),
+            &macos_intel
+        )];
+        assert![!target_matches_schema(("darwin", "arm64", "zip"), &macos)];
+
+        let target = BuildTarget {
+            platform: "windows".to_string(),
+            architecture: Some("x86_64".to_string()),
+        };
+        assert![target.matches(&windows)];
+        assert![!target.matches(&windows_arm)];
+    }
+
+    #[test]
+    fn test_filter_schemas_by_targets() {
+        let schemas = vec![
+            schema("linux", "x86_64", "xz"),
+            schema("windows", "amd64", "zip"),
+            schema("darwin", "arm64", "dmg"),
+        ];
+        let linux = [BuildTarget {
+            platform: "linux".to_string(),
+            architecture: None,
+        }];
+
+        let kept = filter_schemas_by_targets(schemas.clone(), Some(&linux));
+        assert_eq![kept.len(), 1];
+        assert_eq![kept[0].platform, "linux"];
+
+        assert_eq![filter_schemas_by_targets(schemas, None).len(), 3];
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+/// The file formats builds and their sidecar files are distributed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BuildArtifactFormat {
+    /// An xz compressed tarball, used for Linux builds.
+    TarXz,
+    /// A gzip compressed tarball.
+    TarGz,
+    /// A zstd compressed tarball.
+    TarZst,
+    /// A bzip2 compressed tarball, used for older Linux builds.
+    TarBz2,
+    /// An uncompressed tarball.
+    Tar,
+    /// A zip archive, used for Windows builds.
+    Zip,
+    /// A macOS disk image.
+    Dmg,
+    /// A Windows installer package.
+    Msi,
+    /// A Windows installer executable.
+    Exe,
+    /// A Windows app package, used for the Microsoft Store.
+    Msix,
+    /// A self-contained Linux executable.
+    AppImage,
+    /// A SHA256 checksum of another file.
+    Sha256,
+}
+
+impl BuildArtifactFormat {
+    /// Every format, ordered so that longer extensions are checked before the ones they end with.
+    pub const ALL: [Self; 12] = [
+        Self::TarXz,
+        Self::TarGz,
+        Self::TarZst,
+        Self::TarBz2,
+        Self::Tar,
+        Self::Zip,
+        Self::Dmg,
+        Self::Msi,
+        Self::Exe,
+        Self::Msix,
+        Self::AppImage,
+        Self::Sha256,
+    ];
+
+    /// The extension of this format, without a leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::TarXz => "tar.xz",
+            Self::TarGz => "tar.gz",
+            Self::TarZst => "tar.zst",
+            Self::TarBz2 => "tar.bz2",
+            Self::Tar => "tar",
+            Self::Zip => "zip",
+            Self::Dmg => "dmg",
+            Self::Msi => "msi",
+            Self::Exe => "exe",
+            Self::Msix => "msix",
+            Self::AppImage => "AppImage",
+            Self::Sha256 => "sha256",
+        }
+    }
+
+    /// Finds the format of a file by its name.
+    ///
+    /// ```
+    /// use blrs::build_targets::BuildArtifactFormat;
+    ///
+    /// assert_eq![
+    ///     BuildArtifactFormat::from_filename("blender-4.2.0-linux-x64.tar.xz"),
+    ///     Some(BuildArtifactFormat::TarXz)
+    /// ];
+    /// assert_eq![BuildArtifactFormat::from_filename("blender.txt"), None];
+    /// ```
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let lower = filename.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|f| lower.ends_with(&format![".{}", f.extension().to_lowercase()]))
+    }
+
+    /// Finds the format matching an extension, such as `tar.xz` or `zip`.
+    ///
+    /// The shortened extensions used by [`super::READABLE_FILETYPES`], like `xz`, are also accepted.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        match extension.as_str() {
+            "xz" | "txz" => Some(Self::TarXz),
+            "gz" | "tgz" => Some(Self::TarGz),
+            "zst" => Some(Self::TarZst),
+            "bz2" => Some(Self::TarBz2),
+            _ => Self::ALL
+                .into_iter()
+                .find(|f| f.extension().eq_ignore_ascii_case(&extension)),
+        }
+    }
+
+    /// Whether files of this format are actual builds, rather than sidecar files like checksums.
+    pub fn is_installable(&self) -> bool {
+        !matches![self, Self::Sha256 | Self::Msix]
+    }
+
+    /// Whether this format is a Windows installer, which has to be run rather than extracted.
+    pub fn is_installer(&self) -> bool {
+        matches![self, Self::Msi | Self::Exe]
+    }
+
+    /// Whether this format is a tarball.
+    pub fn is_tarball(&self) -> bool {
+        matches![
+            self,
+            Self::TarXz | Self::TarGz | Self::TarZst | Self::TarBz2 | Self::Tar
+        ]
+    }
+
+    /// The platform, as named by the builder, that builds in this format are made for.
+    pub fn platform(&self) -> Option<&'static str> {
+        match self {
+            Self::TarXz | Self::TarBz2 | Self::AppImage => Some("linux"),
+            Self::Zip | Self::Msi | Self::Exe | Self::Msix => Some("windows"),
+            Self::Dmg => Some("darwin"),
+            Self::TarGz | Self::TarZst | Self::Tar | Self::Sha256 => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildArtifactFormat::{self, *};
+
+    #[test]
+    fn test_blender_filenames() {
+        let cases = [
+            (
+                "blender-4.3.0-alpha+main.ddc9f92777cd-linux.x86_64-release.tar.xz",
+                Some(TarXz),
+            ),
+            (
+                "blender-4.2.0-beta+v42.a3b2c1d0e9f8-windows.amd64-release.zip",
+                Some(Zip),
+            ),
+            (
+                "blender-4.2.0-beta+v42.a3b2c1d0e9f8-windows.amd64-release.msi",
+                Some(Msi),
+            ),
+            (
+                "blender-4.2.0-beta+v42.a3b2c1d0e9f8-windows.amd64-release.msix",
+                Some(Msix),
+            ),
+            (
+                "blender-4.2.0-alpha+main-PR109522.f723782e3a8c-darwin.arm64-release.dmg",
+                Some(Dmg),
+            ),
+            (
+                "blender-4.2.0-alpha+main-PR109522.f723782e3a8c-darwin.arm64-release.dmg.sha256",
+                Some(Sha256),
+            ),
+            ("blender-2.79b-linux-glibc219-x86_64.tar.bz2", Some(TarBz2)),
+            ("blender-2.79b-windows64.exe", Some(Exe)),
+            ("Blender-4.1.1-x86_64.AppImage", Some(AppImage)),
+            ("blender-4.2.0-release-notes.txt", None),
+        ];
+
+        for (filename, format) in cases {
+            assert_eq![
+                BuildArtifactFormat::from_filename(filename),
+                format,
+                "{}",
+                filename
+            ];
+        }
+    }
+
+    #[test]
+    fn test_installable() {
+        assert![TarXz.is_installable()];
+        assert![Msi.is_installable() && Msi.is_installer()];
+        assert![!Sha256.is_installable()];
+        assert![!Msix.is_installable()];
+        assert_eq![BuildArtifactFormat::from_extension("xz"), Some(TarXz)];
+        assert_eq![
+            BuildArtifactFormat::from_extension("appimage"),
+            Some(AppImage)
+        ];
+    }
+}
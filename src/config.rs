@@ -1,13 +1,31 @@
-use std::{path::PathBuf, sync::LazyLock, time::Duration};
+/// Where BLRS keeps its config and data by default.
+pub mod paths;
+mod validation;
+
+pub use validation::ConfigError;
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::Duration,
+};
 
 use chrono::{DateTime, Utc};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 
-use crate::fetching::{
-    authentication::GithubAuthentication,
-    build_repository::{BuildRepo, DEFAULT_REPOS},
-    random_ua,
+use crate::{
+    atomic::write_atomically,
+    build_targets::{default_extension, filter_schemas_by_targets, BuildTarget},
+    fetching::{
+        authentication::GithubAuthentication,
+        build_repository::{BuildRepo, DEFAULT_REPOS},
+        build_schemas::BlenderBuildSchema,
+        random_ua, RemoteBuild,
+    },
+    info::launching::OSLaunchTarget,
 };
 
 #[cfg(feature = "figment")]
@@ -15,13 +33,14 @@ use figment::{
     providers::{Format, Serialized, Toml},
     Figment,
 };
-#[cfg(feature = "figment")]
-use std::path::Path;
 
 /// This static variable holds the project's directory structure.
 pub static PROJECT_DIRS: LazyLock<ProjectDirs> =
     LazyLock::new(|| ProjectDirs::from("", "zeptofine", "blrs").unwrap());
 
+/// The name of the config file inside the config folder.
+pub const CONFIG_FILENAME: &str = "config.toml";
+
 /// Ensures that the config folder exists for BLRS configuration files.
 pub fn ensure_config_folder_exists() -> Result<(), std::io::Error> {
     std::fs::create_dir_all(PROJECT_DIRS.config_local_dir())
@@ -44,7 +63,7 @@ pub fn ensure_config_folder_exists() -> Result<(), std::io::Error> {
 /// + ...
 ///```
 pub static DEFAULT_LIBRARY_FOLDER: LazyLock<PathBuf> =
-    LazyLock::new(|| PROJECT_DIRS.data_dir().to_path_buf().join("builds"));
+    LazyLock::new(|| paths::data_dir().join("builds"));
 
 /// The structure of the remote repos folder where repo cache .json files are stored.
 ///```txt
@@ -58,7 +77,24 @@ pub static DEFAULT_LIBRARY_FOLDER: LazyLock<PathBuf> =
 /// + ...
 ///```
 pub static DEFAULT_REPOS_FOLDER: LazyLock<PathBuf> =
-    LazyLock::new(|| PROJECT_DIRS.data_dir().to_path_buf().join("remote-repos"));
+    LazyLock::new(|| paths::data_dir().join("remote-repos"));
+
+/// The folder builds are downloaded and extracted in before being moved into the library.
+/// Each install gets its own folder, which is removed once the install finishes.
+///```txt
+/// staging
+/// |
+/// +-<install_uuid>
+/// | |
+/// | +-<archive>
+/// | +-<extracted>
+/// + ...
+///```
+pub static DEFAULT_STAGING_FOLDER: LazyLock<PathBuf> =
+    LazyLock::new(|| paths::cache_dir().join("staging"));
+
+/// The user agent used when [`BLRSConfig::user_agent`] is not set, picked once per process.
+static SESSION_USER_AGENT: LazyLock<String> = LazyLock::new(random_ua);
 
 /// The interval at which to check for build repo updates (6 hours).
 pub static FETCH_INTERVAL: Duration = Duration::from_secs(60 * 60 * 6);
@@ -70,6 +106,13 @@ pub struct BLRSPaths {
     pub library: PathBuf,
     /// The path that holds all of the repo cache .json files.
     pub remote_repos: PathBuf,
+    /// The path downloads are staged in before their builds are moved into the library.
+    #[serde(default = "default_staging")]
+    pub staging: PathBuf,
+}
+
+fn default_staging() -> PathBuf {
+    DEFAULT_STAGING_FOLDER.clone()
 }
 
 impl BLRSPaths {
@@ -77,6 +120,107 @@ impl BLRSPaths {
     pub fn path_to_repo(&self, br: &BuildRepo) -> PathBuf {
         self.library.join(&br.repo_id)
     }
+
+    /// Returns the path to a specific repository's cache file based on its ID.
+    pub fn path_to_repo_cache(&self, br: &BuildRepo) -> PathBuf {
+        self.remote_repos.join(br.repo_id.clone() + ".json")
+    }
+
+    /// Returns the folder a remote build is installed to, `<library>/<repo_id>/<full_version>`.
+    ///
+    /// [`crate::repos::read_repos`] finds builds by the folders in `<library>/<repo_id>`, so a build
+    /// installed here is listed under that repo.
+    ///
+    /// ```
+    /// use blrs::{BLRSPaths, RemoteBuild};
+    ///
+    /// let paths = BLRSPaths {
+    ///     library: "builds".into(),
+    ///     remote_repos: "remote-repos".into(),
+    ///     staging: "staging".into(),
+    /// };
+    /// let build = RemoteBuild::from_filename(
+    ///     "https://example.com".to_string(),
+    ///     "blender-4.3.0-alpha+main.ddc9f92777cd-linux.x86_64-release.tar.xz",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq![
+    ///     paths.install_path_for("daily", &build),
+    ///     std::path::Path::new("builds/daily/4.3.0-alpha+daily.ddc9f92777cd")
+    /// ];
+    /// ```
+    pub fn install_path_for(&self, repo_id: &str, build: &RemoteBuild) -> PathBuf {
+        self.library
+            .join(repo_id)
+            .join(build.basic.ver.v().to_string())
+    }
+
+    /// Removes everything in the staging folder, such as downloads left behind by an interrupted install.
+    ///
+    /// This should not be called while builds are being installed, as their downloads would be removed.
+    pub fn clear_staging(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.staging) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Moves a repo's library folder and cache file from `old_id` to `new_id`, so they aren't orphaned
+    /// when a [`BuildRepo::repo_id`] changes.
+    ///
+    /// Either of them may be missing, in which case it is skipped. The rename is rejected with
+    /// [`io::ErrorKind::AlreadyExists`] if `new_id` already has a library folder or cache file, and the
+    /// library folder is moved back if the cache file could not be moved.
+    ///
+    /// ```
+    /// use blrs::BLRSPaths;
+    ///
+    /// let dir = std::env::temp_dir().join(format!["blrs-doc-{}", uuid::Uuid::new_v4()]);
+    /// let paths = BLRSPaths {
+    ///     library: dir.join("builds"),
+    ///     remote_repos: dir.join("remote-repos"),
+    ///     staging: dir.join("staging"),
+    /// };
+    /// std::fs::create_dir_all(paths.library.join("daily")).unwrap();
+    /// std::fs::create_dir_all(paths.library.join("taken")).unwrap();
+    ///
+    /// paths.rename_repo("daily", "builder").unwrap();
+    /// assert![paths.library.join("builder").is_dir()];
+    /// assert![paths.rename_repo("builder", "taken").is_err()];
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn rename_repo(&self, old_id: &str, new_id: &str) -> io::Result<()> {
+        let (old_folder, new_folder) = (self.library.join(old_id), self.library.join(new_id));
+        let (old_cache, new_cache) = (
+            self.remote_repos.join(format!["{}.json", old_id]),
+            self.remote_repos.join(format!["{}.json", new_id]),
+        );
+
+        for new in [&new_folder, &new_cache] {
+            if new.symlink_metadata().is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!["{:?} already exists", new],
+                ));
+            }
+        }
+
+        let move_folder = old_folder.symlink_metadata().is_ok();
+        if move_folder {
+            fs::rename(&old_folder, &new_folder)?;
+        }
+        if old_cache.symlink_metadata().is_ok() {
+            if let Err(e) = fs::rename(&old_cache, &new_cache) {
+                if move_folder {
+                    fs::rename(&new_folder, &old_folder)?;
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for BLRSPaths {
@@ -84,17 +228,60 @@ impl Default for BLRSPaths {
         Self {
             library: DEFAULT_LIBRARY_FOLDER.clone(),
             remote_repos: DEFAULT_REPOS_FOLDER.clone(),
+            staging: DEFAULT_STAGING_FOLDER.clone(),
         }
     }
 }
 
-/// Stores information about the last build launched and when the build repos were last checked.
+/// The most launches [`History::recent_launches`] keeps.
+pub const MAX_RECENT_LAUNCHES: usize = 10;
+
+/// Stores information about the builds recently launched and when the build repos were last checked.
 #[derive(Default, Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct History {
-    /// The last build that was launched.
-    pub last_launched_build: Option<PathBuf>,
+    /// The builds that were launched most recently, newest first, with when they were launched.
+    ///
+    /// Each build is only listed once, and at most [`MAX_RECENT_LAUNCHES`] are kept.
+    #[serde(default)]
+    pub recent_launches: Vec<(PathBuf, DateTime<Utc>)>,
     /// The last time the build repos were checked for updates.
     pub last_time_checked: Option<DateTime<Utc>>,
+    /// The last launched build of configs written before `recent_launches` existed.
+    #[serde(default, rename = "last_launched_build", skip_serializing)]
+    legacy_last_launched: Option<PathBuf>,
+}
+
+impl History {
+    /// Records that the build at `path` was just launched, moving it to the front of the recent launches.
+    ///
+    /// ```
+    /// use blrs::config::History;
+    ///
+    /// let mut history = History::default();
+    /// history.push_launch("builds/a".into());
+    /// history.push_launch("builds/b".into());
+    /// history.push_launch("builds/a".into());
+    ///
+    /// assert_eq![history.recent_launches.len(), 2];
+    /// assert_eq![history.last_launched_build().unwrap().to_str(), Some("builds/a")];
+    /// ```
+    pub fn push_launch(&mut self, path: PathBuf) {
+        self.push_launch_at(path, Utc::now());
+    }
+
+    fn push_launch_at(&mut self, path: PathBuf, time: DateTime<Utc>) {
+        self.recent_launches.retain(|(p, _)| *p != path);
+        self.recent_launches.insert(0, (path, time));
+        self.recent_launches.truncate(MAX_RECENT_LAUNCHES);
+    }
+
+    /// The build that was launched last.
+    pub fn last_launched_build(&self) -> Option<&PathBuf> {
+        self.recent_launches
+            .first()
+            .map(|(p, _)| p)
+            .or(self.legacy_last_launched.as_ref())
+    }
 }
 
 // TODO: Encrypt the github authentication somehow
@@ -103,13 +290,28 @@ pub struct History {
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct BLRSConfig {
     /// The user agent string used by BLRS when making network requests.
-    pub user_agent: String,
+    ///
+    /// When unset, a random user agent is used, see [`BLRSConfig::user_agent`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
     /// Defines paths for BLRS data storage.
     pub paths: BLRSPaths,
     /// A list of BuildRepo structs defining the available build repositories.
     pub repos: Vec<BuildRepo>,
     /// Contains information about the last launched build and repo update checks.
     pub history: History,
+    /// The targets to keep when caching fetched builds. `None` keeps every target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_targets: Option<Vec<BuildTarget>>,
+    /// The extension to prefer for each platform when a build has several variants, such as
+    /// `linux = "AppImage"`. Platforms are named like the builder names them ("linux", "windows", "darwin").
+    ///
+    /// Platforms that aren't listed use [`default_extension`].
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub preferred_extensions: HashMap<String, String>,
+    /// The launch target to use instead of the current OS's default, such as Windows without a console.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_os_launch: Option<OSLaunchTarget>,
     /// Authentication details for GitHub
     gh_auth: Option<GithubAuthentication>,
 }
@@ -117,10 +319,13 @@ pub struct BLRSConfig {
 impl Default for BLRSConfig {
     fn default() -> Self {
         Self {
-            user_agent: random_ua(),
+            user_agent: None,
             paths: Default::default(),
             repos: DEFAULT_REPOS.clone().into_iter().collect(),
             history: Default::default(),
+            fetch_targets: None,
+            preferred_extensions: HashMap::new(),
+            default_os_launch: None,
             gh_auth: Default::default(),
         }
     }
@@ -134,11 +339,154 @@ impl BLRSConfig {
     pub fn default_figment(config_folder: Option<&Path>) -> Figment {
         Figment::new()
             .merge(Serialized::defaults(BLRSConfig::default()))
-            .merge(Toml::file(
-                config_folder
-                    .unwrap_or_else(|| PROJECT_DIRS.config_local_dir())
-                    .join("config.toml"),
-            ))
+            .merge(Toml::file(match config_folder {
+                Some(folder) => folder.join(CONFIG_FILENAME),
+                None => paths::default_config_file(),
+            }))
+    }
+
+    /// Prepares a fresh install by creating the library, the remote repos folder, and the config folder,
+    /// and writing this config to `config.toml` if there is none yet.
+    ///
+    /// Existing folders and config files are left alone, so this is safe to call on every startup.
+    pub fn ensure_initialized(&self) -> io::Result<()> {
+        self.ensure_initialized_in(PROJECT_DIRS.config_local_dir())
+    }
+
+    /// Like [`BLRSConfig::ensure_initialized`], but with a custom config folder.
+    ///
+    /// ```
+    /// use blrs::{BLRSConfig, BLRSPaths};
+    ///
+    /// let root = std::env::temp_dir().join(format!["blrs-doctest-{}", uuid::Uuid::new_v4()]);
+    /// let mut config = BLRSConfig::default();
+    /// config.paths = BLRSPaths {
+    ///     library: root.join("builds"),
+    ///     remote_repos: root.join("remote-repos"),
+    ///     staging: root.join("staging"),
+    /// };
+    ///
+    /// config.ensure_initialized_in(&root.join("config")).unwrap();
+    /// config.ensure_initialized_in(&root.join("config")).unwrap();
+    /// assert![root.join("builds").is_dir()];
+    /// assert![root.join("config/config.toml").is_file()];
+    /// # std::fs::remove_dir_all(root).unwrap();
+    /// ```
+    pub fn ensure_initialized_in(&self, config_folder: &Path) -> io::Result<()> {
+        fs::create_dir_all(&self.paths.library)?;
+        fs::create_dir_all(&self.paths.remote_repos)?;
+        fs::create_dir_all(config_folder)?;
+
+        let config_file = config_folder.join(CONFIG_FILENAME);
+        if !config_file.exists() {
+            let contents = toml::to_string_pretty(self)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            write_atomically(&config_file, contents.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Filters fetched build schemas down to the configured [`BLRSConfig::fetch_targets`].
+    ///
+    /// This should be used before writing a repo cache so that unused targets aren't stored.
+    pub fn filter_fetched(&self, schemas: Vec<BlenderBuildSchema>) -> Vec<BlenderBuildSchema> {
+        filter_schemas_by_targets(schemas, self.fetch_targets.as_deref())
+    }
+
+    /// The extension to prefer for builds of `platform`, from [`BLRSConfig::preferred_extensions`] or
+    /// [`default_extension`]. Use it with [`Variants::best_for_target`] to pick a build's variant.
+    ///
+    /// [`Variants::best_for_target`]: crate::repos::Variants::best_for_target
+    pub fn preferred_extension(&self, platform: &str) -> Option<&str> {
+        self.preferred_extensions
+            .get(platform)
+            .map(String::as_str)
+            .or_else(|| default_extension(platform))
+    }
+
+    /// The user agent to make requests with: [`BLRSConfig::user_agent`] if it is set, or a random one that
+    /// stays the same for the rest of the process.
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(&SESSION_USER_AGENT)
+    }
+
+    /// The launch target builds should be launched with, from [`BLRSConfig::default_os_launch`] or
+    /// [`OSLaunchTarget::try_default`].
+    pub fn os_launch_target(&self) -> Option<OSLaunchTarget> {
+        self.default_os_launch
+            .clone()
+            .or_else(OSLaunchTarget::try_default)
+    }
+
+    /// Layers `other` on top of this config, for example a project's config over the user's.
+    ///
+    /// Fields of `other` that are set to something other than their defaults take precedence.
+    /// Repos are merged by `repo_id`, with `other`'s version of a repo replacing this one's, and
+    /// preferred extensions are merged by platform. Repos of `other` that are identical to one of
+    /// [`DEFAULT_REPOS`] are skipped, so layers loaded with [`BLRSConfig::default_figment`] don't undo
+    /// changes made to the default repos.
+    /// The history keeps the most recent check time, and the most recent launches of both.
+    ///
+    /// ```
+    /// use blrs::{fetching::build_repository::BuildRepo, BLRSConfig};
+    ///
+    /// let mut user = BLRSConfig::default();
+    /// let mut project = BLRSConfig::default();
+    /// project.repos = vec![BuildRepo {
+    ///     repo_id: "studio".to_string(),
+    ///     url: "https://builds.example.com/studio.json".to_string(),
+    ///     ..user.repos[0].clone()
+    /// }];
+    /// project.preferred_extensions.insert("linux".to_string(), "AppImage".to_string());
+    ///
+    /// let repo_count = user.repos.len();
+    /// user.merge(project);
+    /// assert_eq![user.repos.len(), repo_count + 1];
+    /// assert_eq![user.preferred_extension("linux"), Some("AppImage")];
+    /// ```
+    pub fn merge(&mut self, other: BLRSConfig) {
+        if other.user_agent.is_some() {
+            self.user_agent = other.user_agent;
+        }
+        if other.paths != BLRSPaths::default() {
+            self.paths = other.paths;
+        }
+
+        for repo in other.repos {
+            if DEFAULT_REPOS.contains(&repo) {
+                continue;
+            }
+            match self.repos.iter_mut().find(|r| r.repo_id == repo.repo_id) {
+                Some(existing) => *existing = repo,
+                None => self.repos.push(repo),
+            }
+        }
+
+        self.history.last_time_checked = self
+            .history
+            .last_time_checked
+            .max(other.history.last_time_checked);
+        let mut launches = std::mem::take(&mut self.history.recent_launches);
+        launches.extend(other.history.recent_launches);
+        launches.sort_by_key(|(_, time)| *time);
+        for (path, time) in launches {
+            self.history.push_launch_at(path, time);
+        }
+        if other.history.legacy_last_launched.is_some() {
+            self.history.legacy_last_launched = other.history.legacy_last_launched;
+        }
+
+        if other.fetch_targets.is_some() {
+            self.fetch_targets = other.fetch_targets;
+        }
+        self.preferred_extensions.extend(other.preferred_extensions);
+        if other.default_os_launch.is_some() {
+            self.default_os_launch = other.default_os_launch;
+        }
+        if other.gh_auth.is_some() {
+            self.gh_auth = other.gh_auth;
+        }
     }
 
     /// A public method for updating the github authentication.
@@ -150,7 +498,7 @@ impl BLRSConfig {
     #[cfg(feature = "reqwest")]
     #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
     pub fn client_builder(&self, use_gh_auth: bool) -> reqwest::ClientBuilder {
-        let user_agent: &str = &self.user_agent;
+        let user_agent = self.user_agent();
 
         let mut r = reqwest::ClientBuilder::new().user_agent(user_agent);
 
@@ -172,3 +520,33 @@ impl BLRSConfig {
         r
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BLRSConfig;
+
+    #[test]
+    fn test_merge_keeps_customizations() {
+        let mut user = BLRSConfig {
+            user_agent: Some("my-agent".to_string()),
+            ..Default::default()
+        };
+        user.repos[0].branch_filter = Some(vec!["main".to_string()]);
+        user.repos[0].user_agent = Some("repo-agent".to_string());
+        let customized = user.repos.clone();
+
+        user.merge(BLRSConfig::default());
+        assert_eq![user.user_agent(), "my-agent"];
+        assert_eq![user.repos, customized];
+
+        user.merge(BLRSConfig {
+            user_agent: Some("project-agent".to_string()),
+            ..Default::default()
+        });
+        assert_eq![user.user_agent(), "project-agent"];
+        assert_eq![
+            BLRSConfig::default().user_agent(),
+            BLRSConfig::default().user_agent()
+        ];
+    }
+}
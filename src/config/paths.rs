@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use super::{CONFIG_FILENAME, PROJECT_DIRS};
+
+/// The folder BLRS reads its config from by default.
+pub fn config_dir() -> PathBuf {
+    PROJECT_DIRS.config_local_dir().to_path_buf()
+}
+
+/// The folder BLRS stores its library and repo caches in by default.
+pub fn data_dir() -> PathBuf {
+    PROJECT_DIRS.data_dir().to_path_buf()
+}
+
+/// The folder BLRS keeps transient files in by default, like downloads that are being installed.
+pub fn cache_dir() -> PathBuf {
+    PROJECT_DIRS.cache_dir().to_path_buf()
+}
+
+/// The config file [`super::BLRSConfig::default_figment`] reads when no config folder is given.
+///
+/// ```
+/// use blrs::config::{paths, CONFIG_FILENAME};
+///
+/// assert![paths::default_config_file().starts_with(paths::config_dir())];
+/// assert![paths::default_config_file().ends_with(CONFIG_FILENAME)];
+/// ```
+pub fn default_config_file() -> PathBuf {
+    config_dir().join(CONFIG_FILENAME)
+}
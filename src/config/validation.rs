@@ -0,0 +1,107 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use thiserror::Error;
+use url::Url;
+
+use super::BLRSConfig;
+
+/// A problem found in a [`BLRSConfig`] by [`BLRSConfig::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConfigError {
+    /// A repo's URL could not be parsed.
+    #[error("The url of repo {repo_id:?} is invalid ({url:?}): {reason}")]
+    InvalidRepoUrl {
+        /// The ID of the repo.
+        repo_id: String,
+        /// The URL that failed to parse.
+        url: String,
+        /// Why the URL failed to parse.
+        reason: String,
+    },
+    /// More than one repo uses the same ID.
+    #[error("The repo ID {0:?} is used more than once")]
+    DuplicateRepoId(String),
+    /// A repo ID is empty.
+    #[error("A repo has an empty ID")]
+    EmptyRepoId,
+    /// A path that should be a directory points to something else.
+    #[error("{0:?} exists but is not a directory")]
+    NotADirectory(PathBuf),
+    /// The user agent is empty.
+    #[error("The user agent is empty")]
+    EmptyUserAgent,
+    /// A repo overrides the user agent with an empty one.
+    #[error("Repo {0:?} has an empty user agent")]
+    EmptyRepoUserAgent(String),
+}
+
+impl BLRSConfig {
+    /// Checks the config for problems that would cause failures later on.
+    ///
+    /// Every problem is collected instead of stopping at the first one, so they can all be reported at once.
+    ///
+    /// ```
+    /// use blrs::{config::ConfigError, BLRSConfig};
+    ///
+    /// let mut config = BLRSConfig::default();
+    /// assert![config.validate().is_ok()];
+    ///
+    /// config.user_agent = Some(String::new());
+    /// config.repos.push(config.repos[0].clone());
+    /// let errors = config.validate().unwrap_err();
+    /// assert![errors.contains(&ConfigError::EmptyUserAgent)];
+    /// assert_eq![errors.len(), 2];
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = vec![];
+
+        if self
+            .user_agent
+            .as_ref()
+            .is_some_and(|ua| ua.trim().is_empty())
+        {
+            errors.push(ConfigError::EmptyUserAgent);
+        }
+
+        for path in [
+            &self.paths.library,
+            &self.paths.remote_repos,
+            &self.paths.staging,
+        ] {
+            if path.exists() && !path.is_dir() {
+                errors.push(ConfigError::NotADirectory(path.clone()));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for repo in &self.repos {
+            if repo.repo_id.is_empty() {
+                errors.push(ConfigError::EmptyRepoId);
+            } else if !seen.insert(&repo.repo_id) {
+                errors.push(ConfigError::DuplicateRepoId(repo.repo_id.clone()));
+            }
+
+            if let Err(e) = Url::parse(&repo.url) {
+                errors.push(ConfigError::InvalidRepoUrl {
+                    repo_id: repo.repo_id.clone(),
+                    url: repo.url.clone(),
+                    reason: e.to_string(),
+                });
+            }
+
+            if repo
+                .user_agent
+                .as_ref()
+                .is_some_and(|ua| ua.trim().is_empty())
+            {
+                errors.push(ConfigError::EmptyRepoUserAgent(repo.repo_id.clone()));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
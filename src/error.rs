@@ -0,0 +1,128 @@
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{
+    fetching::{build_repository::FetchError, checksums::ParseError},
+    search::FromError,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    config::ConfigError,
+    info::{
+        launching::{ArgGenerationError, ParseOSLaunchTargetError},
+        BlendReadErr, ProbeError,
+    },
+};
+
+/// Any error returned by blrs.
+///
+/// Every subsystem keeps its own error type, and each converts into this one with `?`,
+/// so callers that don't care where an error came from only need to handle one type.
+///
+/// ```
+/// use blrs::{search::VersionSearchQuery, BlrsError};
+///
+/// fn parse(query: &str) -> Result<VersionSearchQuery, BlrsError> {
+///     Ok(VersionSearchQuery::try_from(query)?)
+/// }
+///
+/// assert![parse("4.2.0").is_ok()];
+/// assert![matches![parse("four"), Err(BlrsError::Query(_))]];
+/// ```
+///
+/// Every problem found by [`crate::BLRSConfig::validate`] converts at once:
+///
+/// ```
+/// use blrs::{BlrsError, BLRSConfig};
+///
+/// fn check(config: &BLRSConfig) -> Result<(), BlrsError> {
+///     Ok(config.validate()?)
+/// }
+///
+/// let mut config = BLRSConfig::default();
+/// config.user_agent = Some(String::new());
+/// assert![matches![check(&config), Err(BlrsError::InvalidConfig(errors)) if errors.len() == 1]];
+/// ```
+#[derive(Debug, Error)]
+pub enum BlrsError {
+    /// A filesystem or other IO error.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Fetching a repository failed.
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+    /// A search query could not be parsed.
+    #[error(transparent)]
+    Query(#[from] FromError),
+    /// A checksum file could not be read.
+    #[error(transparent)]
+    Checksum(#[from] ParseError),
+    /// The configuration is invalid.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    /// The configuration has several problems, as returned by [`crate::BLRSConfig::validate`].
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+    #[error("The configuration is invalid: {}", .0.iter().join("; "))]
+    InvalidConfig(Vec<ConfigError>),
+    /// A launch target could not be parsed.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+    #[error(transparent)]
+    LaunchTarget(#[from] ParseOSLaunchTargetError),
+    /// Launch arguments could not be generated.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+    #[error(transparent)]
+    ArgGeneration(#[from] ArgGenerationError),
+    /// A build could not be probed.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+    #[error(transparent)]
+    Probe(#[from] ProbeError),
+    /// The header of a .blend file could not be read. See [`BlendReadErr`].
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+    #[error("Failed to read the blend file: {error}")]
+    BlendFile {
+        /// The error from reading the file uncompressed.
+        #[source]
+        error: std::io::Error,
+        /// The errors from reading the file with gzip and zstd, if compressed blends were tried.
+        compressed: Option<(std::io::Error, std::io::Error)>,
+    },
+    /// Installing a build failed.
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(feature = "reqwest", not(target_arch = "wasm32"))))
+    )]
+    #[error(transparent)]
+    Install(#[from] crate::install::InstallError),
+    /// A build's signature could not be verified.
+    #[cfg(all(
+        feature = "reqwest",
+        feature = "signature-check",
+        not(target_arch = "wasm32")
+    ))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "signature-check")))]
+    #[error(transparent)]
+    Signature(#[from] crate::install::VerifyError),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<Vec<ConfigError>> for BlrsError {
+    fn from(errors: Vec<ConfigError>) -> Self {
+        Self::InvalidConfig(errors)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<BlendReadErr> for BlrsError {
+    fn from((error, compressed): BlendReadErr) -> Self {
+        Self::BlendFile { error, compressed }
+    }
+}
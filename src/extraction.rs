@@ -0,0 +1,5 @@
+mod addons;
+mod file_extractor;
+mod tar;
+
+pub use file_extractor::{ExtractOverwritePolicy, FileExtractor};
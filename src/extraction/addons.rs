@@ -0,0 +1,46 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::LocalBuild;
+
+use super::FileExtractor;
+
+impl LocalBuild {
+    /// Installs an addon into the build's `scripts/addons` folder, inside of its [`LocalBuild::resource_dir`].
+    ///
+    /// The addon can either be a single `.py` file, which is copied, or a `.zip` holding a package
+    /// (or a single `.py` file), which is extracted.
+    ///
+    /// Returns the path of the installed addon.
+    pub fn install_addon(&self, addon: &Path) -> io::Result<PathBuf> {
+        let addons_dir = self
+            .resource_dir()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!["Could not find the resource folder of {:?}", self.folder],
+                )
+            })?
+            .join("scripts")
+            .join("addons");
+        fs::create_dir_all(&addons_dir)?;
+
+        let is_py = addon
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("py"));
+
+        match is_py {
+            true => {
+                let file_name = addon.file_name().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "Addon path has no file name")
+                })?;
+                let target = addons_dir.join(file_name);
+                fs::copy(addon, &target)?;
+                Ok(target)
+            }
+            false => FileExtractor::new(addon)?.extract_to(&addons_dir),
+        }
+    }
+}
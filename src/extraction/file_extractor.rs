@@ -0,0 +1,456 @@
+use std::{
+    cell::Cell,
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use log::debug;
+use regex::Regex;
+use zip::ZipArchive;
+
+use crate::build_targets::BuildArtifactFormat;
+
+use super::tar;
+
+/// The archive layouts [`FileExtractor`] knows how to read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarXz,
+    #[cfg(feature = "extraction")]
+    TarBz2,
+    #[cfg(feature = "extraction")]
+    TarZst,
+}
+
+impl ArchiveKind {
+    fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let format = BuildArtifactFormat::from_filename(name)
+            .or_else(|| BuildArtifactFormat::from_extension(path.extension()?.to_str()?))?;
+
+        match format {
+            BuildArtifactFormat::Zip => Some(Self::Zip),
+            BuildArtifactFormat::TarXz => Some(Self::TarXz),
+            BuildArtifactFormat::Tar => Some(Self::Tar),
+            #[cfg(feature = "extraction")]
+            BuildArtifactFormat::TarBz2 => Some(Self::TarBz2),
+            #[cfg(feature = "extraction")]
+            BuildArtifactFormat::TarZst => Some(Self::TarZst),
+            _ => None,
+        }
+    }
+}
+
+/// Converts a relative archive path to a `/` separated string, for matching against patterns.
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A reader that keeps count of how many bytes have passed through it.
+struct CountingReader<'a, R: Read> {
+    inner: R,
+    count: &'a Cell<u64>,
+}
+
+impl<R: Read> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n as u64);
+        Ok(n)
+    }
+}
+
+/// What [`FileExtractor`] does with files that already exist in the destination.
+///
+/// Skipping files lets an extraction that was interrupted be resumed without writing everything again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExtractOverwritePolicy {
+    /// Every file is written, replacing what is already there.
+    #[default]
+    Always,
+    /// Files that already exist are left as they are, even if they were only partially written.
+    SkipExisting,
+    /// Files that already exist with the same size as in the archive are left as they are.
+    SkipIfSameSize,
+}
+
+impl ExtractOverwritePolicy {
+    /// Whether the file at `out` should be left as it is instead of writing the `size` bytes from the archive.
+    fn skips(&self, out: &Path, size: u64) -> bool {
+        let existing = || fs::symlink_metadata(out).ok().filter(|m| m.is_file());
+        match self {
+            ExtractOverwritePolicy::Always => false,
+            ExtractOverwritePolicy::SkipExisting => existing().is_some(),
+            ExtractOverwritePolicy::SkipIfSameSize => existing().is_some_and(|m| m.len() == size),
+        }
+    }
+}
+
+/// Extracts downloaded build archives.
+///
+/// Supports `.zip`, `.tar`, and `.tar.xz` archives, and `.tar.bz2` and `.tar.zst` archives
+/// with the `extraction` feature.
+/// Compressed tarballs are decompressed once to a temporary file, which is reused
+/// across extractions and removed when the extractor is dropped.
+#[derive(Debug)]
+pub struct FileExtractor {
+    archive: PathBuf,
+    kind: ArchiveKind,
+    decompressed: Option<PathBuf>,
+    overwrite: ExtractOverwritePolicy,
+}
+
+impl FileExtractor {
+    /// Creates a new extractor for the given archive.
+    ///
+    /// Returns an [`io::ErrorKind::Unsupported`] error if the archive format is not recognized.
+    pub fn new<P>(archive: P) -> io::Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let archive = archive.into();
+        let kind = ArchiveKind::from_path(&archive).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!["Unsupported archive format: {:?}", archive],
+            )
+        })?;
+
+        Ok(Self {
+            archive,
+            kind,
+            decompressed: None,
+            overwrite: ExtractOverwritePolicy::default(),
+        })
+    }
+
+    /// Sets what happens to files that already exist in the destination. Defaults to [`ExtractOverwritePolicy::Always`].
+    pub fn with_overwrite(mut self, overwrite: ExtractOverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// The path to the archive being extracted.
+    pub fn archive(&self) -> &Path {
+        &self.archive
+    }
+
+    /// Opens the archive's uncompressed tarball, decompressing it first if necessary.
+    fn tar_file(&mut self) -> io::Result<BufReader<File>> {
+        let path = match (self.kind, &self.decompressed) {
+            (ArchiveKind::Tar, _) => self.archive.clone(),
+            (_, Some(p)) => p.clone(),
+            (ArchiveKind::Zip, None) => unreachable!(),
+            (kind, None) => {
+                let p = std::env::temp_dir().join(format!["blrs-{}.tar", uuid::Uuid::new_v4()]);
+                debug!["Decompressing {:?} to {:?}", self.archive, p];
+
+                let mut input = BufReader::new(File::open(&self.archive)?);
+                let mut output = BufWriter::new(File::create(&p)?);
+                let decompressed = match kind {
+                    ArchiveKind::TarXz => lzma_rs::xz_decompress(&mut input, &mut output)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+                    #[cfg(feature = "extraction")]
+                    ArchiveKind::TarBz2 => {
+                        io::copy(&mut bzip2::read::BzDecoder::new(input), &mut output).map(|_| ())
+                    }
+                    #[cfg(feature = "extraction")]
+                    ArchiveKind::TarZst => zstd::stream::copy_decode(input, &mut output),
+                    ArchiveKind::Zip | ArchiveKind::Tar => unreachable!(),
+                }
+                .and_then(|_| io::Write::flush(&mut output));
+                drop(output);
+
+                // A partially decompressed tarball must not be reused by later extractions.
+                if let Err(e) = decompressed {
+                    let _ = fs::remove_file(&p);
+                    return Err(e);
+                }
+                self.decompressed = Some(p.clone());
+                p
+            }
+        };
+
+        Ok(BufReader::new(File::open(path)?))
+    }
+
+    /// Extracts every entry accepted by `filter` into `dest`, returning their paths relative to `dest`.
+    ///
+    /// `on_progress` is called after every entry, see [`FileExtractor::extract_to_with_progress`].
+    fn extract_matching<F, P>(
+        &mut self,
+        dest: &Path,
+        filter: F,
+        mut on_progress: P,
+    ) -> io::Result<Vec<PathBuf>>
+    where
+        F: Fn(&Path) -> bool,
+        P: FnMut(u64, u64),
+    {
+        fs::create_dir_all(dest)?;
+        let mut extracted = vec![];
+
+        match self.kind {
+            ArchiveKind::Zip => {
+                let mut archive = ZipArchive::new(BufReader::new(File::open(&self.archive)?))?;
+                let total = archive.len() as u64;
+                for i in 0..archive.len() {
+                    on_progress(i as u64, total);
+                    let mut file = archive.by_index(i)?;
+                    let Some(relative) = file.enclosed_name() else {
+                        continue;
+                    };
+                    if !filter(&relative) {
+                        continue;
+                    }
+
+                    let out = dest.join(&relative);
+                    if file.is_dir() {
+                        fs::create_dir_all(&out)?;
+                    } else if self.overwrite.skips(&out, file.size()) {
+                        debug!["Skipping existing file {:?}", out];
+                    } else {
+                        if let Some(parent) = out.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        let mut outfile = File::create(&out)?;
+                        io::copy(&mut file, &mut outfile)?;
+                        #[cfg(unix)]
+                        if let Some(mode) = file.unix_mode() {
+                            use std::os::unix::fs::PermissionsExt;
+                            fs::set_permissions(&out, fs::Permissions::from_mode(mode & 0o7777))?;
+                        }
+                    }
+                    extracted.push(relative);
+                }
+                on_progress(total, total);
+            }
+            _ => {
+                let overwrite = self.overwrite;
+                let file = self.tar_file()?;
+                let total = file.get_ref().metadata()?.len();
+                let consumed = Cell::new(0);
+                let reader = CountingReader {
+                    inner: file,
+                    count: &consumed,
+                };
+
+                tar::for_each_entry(reader, |entry, data| {
+                    on_progress(consumed.get(), total);
+                    if let Some(relative) = tar::enclosed_path(&entry.path) {
+                        if !filter(&relative) {
+                            return Ok(true);
+                        }
+                        let skipped = entry.kind == tar::TarEntryKind::File
+                            && overwrite.skips(&dest.join(&relative), entry.size);
+                        if skipped || tar::unpack_entry(entry, data, dest)?.is_some() {
+                            extracted.push(relative);
+                        }
+                    }
+                    Ok(true)
+                })?;
+                on_progress(total, total);
+            }
+        }
+
+        Ok(extracted)
+    }
+
+    /// Extracts the whole archive into `dest`.
+    ///
+    /// Blender archives usually hold a single top-level folder. If the archive holds a single top-level
+    /// entry, the path to that entry is returned. Otherwise, `dest` is returned.
+    pub fn extract_to(&mut self, dest: &Path) -> io::Result<PathBuf> {
+        self.extract_to_with_progress(dest, |_, _| {})
+    }
+
+    /// Extracts the whole archive into `dest`, like [`FileExtractor::extract_to`], reporting progress along the way.
+    ///
+    /// `on_progress` receives the amount processed so far and the total, similar to the `downloaded_bytes`
+    /// and `total_bytes` of [`crate::fetching::fetcher::FetcherState`]. For zip archives these are counted
+    /// in entries, and for tarballs in (decompressed) bytes.
+    pub fn extract_to_with_progress<P>(
+        &mut self,
+        dest: &Path,
+        on_progress: P,
+    ) -> io::Result<PathBuf>
+    where
+        P: FnMut(u64, u64),
+    {
+        let extracted = self.extract_matching(dest, |_| true, on_progress)?;
+
+        let roots: HashSet<_> = extracted
+            .iter()
+            .filter_map(|p| p.components().next())
+            .collect();
+
+        match (roots.len(), roots.into_iter().next()) {
+            (1, Some(root)) => Ok(dest.join(root)),
+            _ => Ok(dest.to_path_buf()),
+        }
+    }
+
+    /// Extracts only the entries whose path inside the archive matches `inner_path_pattern`.
+    ///
+    /// The pattern is a regex matched against `/` separated paths, like
+    /// `^[^/]+/(blender|lib/.*)$` to pull out the linux binary and the libraries it needs.
+    /// This is a lot faster than [`FileExtractor::extract_to`] when only the executable is
+    /// needed, for example to run [`crate::LocalBuild::generate_from_exe`].
+    ///
+    /// Returns the path to the first extracted file, or an [`io::ErrorKind::NotFound`] error if nothing matched.
+    pub fn extract_single(&mut self, inner_path_pattern: &str, dest: &Path) -> io::Result<PathBuf> {
+        let re = Regex::new(inner_path_pattern)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        let extracted = self.extract_matching(dest, |p| re.is_match(&slash_path(p)), |_, _| {})?;
+
+        extracted
+            .into_iter()
+            .map(|p| dest.join(p))
+            .find(|p| p.is_file())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format![
+                        "No entry in {:?} matches {:?}",
+                        self.archive, inner_path_pattern
+                    ],
+                )
+            })
+    }
+}
+
+impl Drop for FileExtractor {
+    fn drop(&mut self) {
+        if let Some(p) = self.decompressed.take() {
+            let _ = fs::remove_file(p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Write};
+
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::{ExtractOverwritePolicy, FileExtractor};
+
+    #[test]
+    fn test_zip_extraction() {
+        let dir = std::env::temp_dir().join(format!["blrs-test-{}", uuid::Uuid::new_v4()]);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("blender-4.3.0-windows.amd64.zip");
+
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.add_directory("blender-4.3.0/", options).unwrap();
+        zip.start_file("blender-4.3.0/blender.exe", options)
+            .unwrap();
+        zip.write_all(b"exe").unwrap();
+        zip.start_file("blender-4.3.0/4.3/readme.txt", options)
+            .unwrap();
+        zip.write_all(b"readme").unwrap();
+        zip.finish().unwrap();
+
+        let mut extractor = FileExtractor::new(&archive).unwrap();
+
+        let exe = extractor
+            .extract_single(r"^[^/]+/blender\.exe$", &dir.join("single"))
+            .unwrap();
+        assert_eq![exe, dir.join("single/blender-4.3.0/blender.exe")];
+        assert![!dir.join("single/blender-4.3.0/4.3").exists()];
+
+        let mut progress = vec![];
+        let root = extractor
+            .extract_to_with_progress(&dir.join("full"), |n, total| progress.push((n, total)))
+            .unwrap();
+        assert_eq![progress.last(), Some(&(3, 3))];
+        assert_eq![root, dir.join("full/blender-4.3.0")];
+        assert![root.join("4.3/readme.txt").is_file()];
+
+        // A file cut short by an interrupted extraction is rewritten, while complete ones are kept
+        std::fs::write(root.join("4.3/readme.txt"), b"read").unwrap();
+        std::fs::write(root.join("blender.exe"), b"EXE").unwrap();
+        FileExtractor::new(&archive)
+            .unwrap()
+            .with_overwrite(ExtractOverwritePolicy::SkipIfSameSize)
+            .extract_to(&dir.join("full"))
+            .unwrap();
+        assert_eq![
+            std::fs::read(root.join("4.3/readme.txt")).unwrap(),
+            b"readme"
+        ];
+        assert_eq![std::fs::read(root.join("blender.exe")).unwrap(), b"EXE"];
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// A tarball holding a single `blender-4.3.0/blender` file.
+    #[cfg(feature = "extraction")]
+    fn small_tar() -> Vec<u8> {
+        let data = b"exe";
+        let mut header = [0u8; 512];
+        header[..21].copy_from_slice(b"blender-4.3.0/blender");
+        header[100..107].copy_from_slice(b"0000755");
+        header[124..135].copy_from_slice(format!["{:011o}", data.len()].as_bytes());
+        header[156] = b'0';
+
+        let mut tar = header.to_vec();
+        tar.extend(data);
+        tar.resize(512 * 4, 0);
+        tar
+    }
+
+    #[cfg(feature = "extraction")]
+    #[test]
+    fn test_compressed_tar_extraction() {
+        let dir = std::env::temp_dir().join(format!["blrs-test-{}", uuid::Uuid::new_v4()]);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let zst = dir.join("blender-4.3.0-linux.x64.tar.zst");
+        std::fs::write(&zst, zstd::encode_all(&small_tar()[..], 0).unwrap()).unwrap();
+        let bz2 = dir.join("blender-4.3.0-linux.x64.tar.bz2");
+        let mut encoder = bzip2::write::BzEncoder::new(
+            File::create(&bz2).unwrap(),
+            bzip2::Compression::default(),
+        );
+        encoder.write_all(&small_tar()).unwrap();
+        encoder.finish().unwrap();
+
+        for (archive, out) in [(zst, "zst"), (bz2, "bz2")] {
+            let root = FileExtractor::new(&archive)
+                .unwrap()
+                .extract_to(&dir.join(out))
+                .unwrap();
+            assert_eq![std::fs::read(root.join("blender")).unwrap(), b"exe"];
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_decompression_is_not_reused() {
+        let dir = std::env::temp_dir().join(format!["blrs-test-{}", uuid::Uuid::new_v4()]);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("broken.tar.xz");
+        std::fs::write(&archive, b"not xz").unwrap();
+
+        let mut extractor = FileExtractor::new(&archive).unwrap();
+        assert![extractor.extract_to(&dir.join("out")).is_err()];
+        assert![extractor.decompressed.is_none()];
+        assert![extractor.extract_to(&dir.join("out")).is_err()];
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
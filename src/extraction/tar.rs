@@ -0,0 +1,262 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+};
+
+const BLOCK_SIZE: u64 = 512;
+
+/// The kind of an entry in a tar archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TarEntryKind {
+    File,
+    Directory,
+    Symlink(PathBuf),
+    Hardlink(PathBuf),
+    Other,
+}
+
+/// A single parsed tar header.
+#[derive(Debug, Clone)]
+pub(crate) struct TarEntry {
+    /// The path of the entry inside the archive.
+    pub path: PathBuf,
+    pub kind: TarEntryKind,
+    /// The unix permission bits of the entry.
+    pub mode: u32,
+    /// The size of the entry's data.
+    pub size: u64,
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn cstr(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn octal(bytes: &[u8]) -> io::Result<u64> {
+    // GNU base-256 encoding for large values
+    if bytes.first().is_some_and(|b| b & 0x80 != 0) {
+        return Ok(bytes[1..]
+            .iter()
+            .fold((bytes[0] & 0x7f) as u64, |acc, b| (acc << 8) | *b as u64));
+    }
+
+    let s = cstr(bytes);
+    let s = s.trim_matches(|c: char| c == ' ' || c == '\0');
+    if s.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(s, 8).map_err(|_| invalid("Invalid octal number in tar header"))
+}
+
+/// Reads the records of a pax extended header, returning the overridden path, link path, and size.
+fn parse_pax(data: &[u8]) -> (Option<String>, Option<String>, Option<u64>) {
+    let (mut path, mut link, mut size) = (None, None, None);
+    let mut rest = data;
+    while let Some(space) = rest.iter().position(|b| *b == b' ') {
+        let Some(len) = std::str::from_utf8(&rest[..space])
+            .ok()
+            .and_then(|l| l.parse::<usize>().ok())
+        else {
+            break;
+        };
+        if len <= space || len > rest.len() {
+            break;
+        }
+
+        let record = String::from_utf8_lossy(&rest[space + 1..len]);
+        let record = record.trim_end_matches('\n');
+        if let Some((key, value)) = record.split_once('=') {
+            match key {
+                "path" => path = Some(value.to_string()),
+                "linkpath" => link = Some(value.to_string()),
+                "size" => size = value.parse().ok(),
+                _ => {}
+            }
+        }
+        rest = &rest[len..];
+    }
+
+    (path, link, size)
+}
+
+fn skip<R: Read>(reader: &mut R, n: u64) -> io::Result<()> {
+    io::copy(&mut reader.by_ref().take(n), &mut io::sink())?;
+    Ok(())
+}
+
+fn padding(size: u64) -> u64 {
+    (BLOCK_SIZE - size % BLOCK_SIZE) % BLOCK_SIZE
+}
+
+/// Walks every entry of a tar stream.
+///
+/// `f` is given each entry along with a reader over its data. Whatever `f` does not read is skipped.
+/// Returning `Ok(false)` from `f` stops the walk early.
+pub(crate) fn for_each_entry<R, F>(mut reader: R, mut f: F) -> io::Result<()>
+where
+    R: Read,
+    F: FnMut(&TarEntry, &mut dyn Read) -> io::Result<bool>,
+{
+    let mut long_name: Option<String> = None;
+    let mut long_link: Option<String> = None;
+    let mut pax_size: Option<u64> = None;
+
+    loop {
+        let mut header = [0u8; BLOCK_SIZE as usize];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        if header.iter().all(|b| *b == 0) {
+            return Ok(());
+        }
+
+        let typeflag = header[156];
+        let mut size = octal(&header[124..136])?;
+        let mode = octal(&header[100..108])? as u32;
+
+        match typeflag {
+            b'L' | b'K' | b'x' => {
+                let mut data = vec![];
+                reader.by_ref().take(size).read_to_end(&mut data)?;
+                skip(&mut reader, padding(size))?;
+                match typeflag {
+                    b'L' => long_name = Some(cstr(&data)),
+                    b'K' => long_link = Some(cstr(&data)),
+                    _ => {
+                        let (path, link, s) = parse_pax(&data);
+                        long_name = path.or(long_name);
+                        long_link = link.or(long_link);
+                        pax_size = s.or(pax_size);
+                    }
+                }
+                continue;
+            }
+            b'g' => {
+                skip(&mut reader, size + padding(size))?;
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some(s) = pax_size.take() {
+            size = s;
+        }
+
+        let path = long_name.take().unwrap_or_else(|| {
+            let name = cstr(&header[0..100]);
+            let prefix = cstr(&header[345..500]);
+            if &header[257..263] == b"ustar\0" && !prefix.is_empty() {
+                format!["{}/{}", prefix, name]
+            } else {
+                name
+            }
+        });
+        let link = long_link.take().unwrap_or_else(|| cstr(&header[157..257]));
+
+        let kind = match typeflag {
+            b'0' | b'\0' | b'7' => TarEntryKind::File,
+            b'5' => TarEntryKind::Directory,
+            b'2' => TarEntryKind::Symlink(PathBuf::from(link)),
+            b'1' => TarEntryKind::Hardlink(PathBuf::from(link)),
+            _ => TarEntryKind::Other,
+        };
+        // Links and directories carry no data, regardless of the size field
+        if !matches![kind, TarEntryKind::File | TarEntryKind::Other] {
+            size = 0;
+        }
+
+        let entry = TarEntry {
+            path: PathBuf::from(path),
+            kind,
+            mode,
+            size,
+        };
+
+        let mut data = reader.by_ref().take(size);
+        let keep_going = f(&entry, &mut data)?;
+        io::copy(&mut data, &mut io::sink())?;
+        skip(&mut reader, padding(size))?;
+
+        if !keep_going {
+            return Ok(());
+        }
+    }
+}
+
+/// Returns the path relative to the archive root, rejecting absolute paths and `..` components.
+pub(crate) fn enclosed_path(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(c) => out.push(c),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    (!out.as_os_str().is_empty()).then_some(out)
+}
+
+/// Writes a single tar entry under `dest`, returning the path it was written to.
+pub(crate) fn unpack_entry(
+    entry: &TarEntry,
+    data: &mut dyn Read,
+    dest: &Path,
+) -> io::Result<Option<PathBuf>> {
+    let Some(relative) = enclosed_path(&entry.path) else {
+        return Ok(None);
+    };
+    let out = dest.join(relative);
+
+    if let Some(parent) = out.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match &entry.kind {
+        TarEntryKind::Directory => fs::create_dir_all(&out)?,
+        TarEntryKind::File => {
+            let mut file = File::create(&out)?;
+            io::copy(data, &mut file)?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&out, fs::Permissions::from_mode(entry.mode & 0o7777))?;
+            }
+        }
+        TarEntryKind::Symlink(target) => {
+            if out.symlink_metadata().is_ok() {
+                fs::remove_file(&out)?;
+            }
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(target, &out)?;
+            #[cfg(not(unix))]
+            {
+                // Symlinks are not portable, so copy the file it points to if it was already extracted
+                let source = out.parent().unwrap_or(dest).join(target);
+                if source.is_file() {
+                    fs::copy(source, &out)?;
+                }
+            }
+        }
+        TarEntryKind::Hardlink(target) => {
+            let Some(target) = enclosed_path(target) else {
+                return Ok(None);
+            };
+            if out.symlink_metadata().is_ok() {
+                fs::remove_file(&out)?;
+            }
+            let source = dest.join(target);
+            fs::hard_link(&source, &out).or_else(|_| fs::copy(&source, &out).map(|_| ()))?;
+        }
+        TarEntryKind::Other => return Ok(None),
+    }
+
+    Ok(Some(out))
+}
@@ -6,6 +6,9 @@ pub mod authentication;
 /// This module contains functions and types necessary for interacting with various repository services.
 pub mod build_repository;
 
+/// The kinds of servers builds can be fetched from.
+pub mod build_source;
+
 /// API schemas the project can recognize.
 pub mod build_schemas;
 
@@ -18,9 +21,31 @@ pub mod checksums;
 pub mod fetcher;
 mod remote_build;
 
+/// Refreshing repo caches in the background.
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "reqwest", not(target_arch = "wasm32"))))
+)]
+pub mod refresh;
+
 pub use remote_build::RemoteBuild;
 
+/// Generates a stable user-agent that identifies the crate, without any random component.
+///
+/// Some services (like GitHub) reject user agents that look random.
+pub fn stable_ua() -> String {
+    format![
+        "{}/{} (+{})",
+        env!["CARGO_PKG_NAME"],
+        env!["CARGO_PKG_VERSION"],
+        env!["CARGO_PKG_REPOSITORY"],
+    ]
+}
+
 /// Generates a random user-agent
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
 pub fn random_ua() -> String {
     format![
         "{}/{}/{}-{}-{}",
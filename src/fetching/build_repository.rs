@@ -1,59 +1,64 @@
 use std::sync::LazyLock;
 
-use log::debug;
+use chrono::{DateTime, Utc};
 
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 #[cfg(feature = "reqwest")]
-use reqwest::{Client, StatusCode, Url};
+use reqwest::{header::HeaderMap, Client, StatusCode, Url};
 
-use super::build_schemas::{
-    BlenderBuildSchema,
-    // github::GithubRelease
-};
+use super::{build_schemas::BlenderBuildSchema, build_source::BuildSource};
 
 /// Enum representing the different types of repositories that can be fetched.
 ///
-/// Each variant corresponds to a specific repository type and has its own method for
-/// deserializing the response data into a list of `BlenderBuildSchema` objects.
+/// Each variant corresponds to a [`BuildSource`] that it delegates fetching and deserializing to.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum RepoType {
-    /// The Blender repository type. Data is expected to be in JSON format.
+    /// The Blender repository type. Data is expected to be in JSON format, see [`super::build_source::BlenderSource`].
     Blender,
-    // /// The GitHub API repository type. Data is also expected to be in JSON format and
-    // /// represents a single release. It is then converted into a list of `BlenderBuildSchema`
-    // /// objects using the `to_build_schemas` method.
-    // GithubAPI,
+    /// The GitHub API repository type. Data is also expected to be in JSON format and
+    /// represents a page of releases, see [`super::build_source::GithubSource`].
+    ///
+    /// Responses from this type of repository may be paginated, see [`BuildSource::is_paginated`].
+    GithubAPI,
 }
 
-impl RepoType {
-    /// Attempts to deserialize the given response data into a list of `BlenderBuildSchema`
-    /// objects, depending on the type of repository specified.
-    ///
-    /// Returns an error if deserialization fails for any reason, or if the response data is
-    /// invalid (e.g. not in JSON format).
-    pub fn try_serialize(&self, data: Vec<u8>) -> Result<Vec<BlenderBuildSchema>, FetchError> {
-        match self {
-            RepoType::Blender => match String::from_utf8(data) {
-                Err(_) => Err(FetchError::InvalidResponse),
-                Ok(s) => match serde_json::from_str(&s) {
-                    Ok(lst) => Ok(lst),
-                    Err(e) => {
-                        debug!["failed to parse string: {:?}", s];
-
-                        Err(FetchError::FailedToDeserialize(e))
-                    }
-                },
-            },
-            // RepoType::GithubAPI => match String::from_utf8(data) {
-            //     Err(_) => Err(FetchError::InvalidResponse),
-            //     Ok(s) => match serde_json::from_str::<GithubRelease>(&s) {
-            //         Ok(release) => Ok(release.to_build_schemas()),
-            //         Err(_) => Err(FetchError::FailedToDeserialize),
-            //     },
-            // },
-        }
-    }
+/// Finds the `rel="next"` link in a `Link` header, as used by GitHub for pagination.
+///
+/// ```
+/// use blrs::fetching::build_repository::next_page_link;
+/// use reqwest::header::{HeaderMap, HeaderValue, LINK};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert(
+///     LINK,
+///     HeaderValue::from_static(
+///         r#"<https://api.github.com/repositories/1/releases?page=2>; rel="next", <https://api.github.com/repositories/1/releases?page=5>; rel="last""#,
+///     ),
+/// );
+/// assert_eq![
+///     next_page_link(&headers).unwrap().as_str(),
+///     "https://api.github.com/repositories/1/releases?page=2"
+/// ];
+/// assert![next_page_link(&HeaderMap::new()).is_none()];
+/// ```
+#[cfg(feature = "reqwest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+pub fn next_page_link(headers: &HeaderMap) -> Option<Url> {
+    headers
+        .get_all(reqwest::header::LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|link| {
+            let (url, params) = link.split_once(';')?;
+            params
+                .split(';')
+                .any(|p| matches![p.trim(), "rel=\"next\"" | "rel=next"])
+                .then(|| Url::parse(url.trim().trim_start_matches('<').trim_end_matches('>')).ok())
+                .flatten()
+        })
 }
 /// Represents a build repository.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -66,12 +71,60 @@ pub struct BuildRepo {
     pub nickname: String,
     /// The type of repository (Blender or GithubAPI).
     pub repo_type: RepoType,
+    /// A user agent to use for this repository instead of the client's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Only builds from these branches are kept when fetching. An empty or absent filter keeps every branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_filter: Option<Vec<String>>,
 }
 
 impl BuildRepo {
+    /// Removes every schema whose branch is not in [`BuildRepo::branch_filter`].
+    ///
+    /// Schemas are kept if either their raw or canonical branch is listed, see [`BlenderBuildSchema::branch`].
+    pub fn filter_branches(&self, schemas: Vec<BlenderBuildSchema>) -> Vec<BlenderBuildSchema> {
+        match &self.branch_filter {
+            Some(branches) if !branches.is_empty() => schemas
+                .into_iter()
+                .filter(|s| branches.iter().any(|b| *b == s.branch || b == s.branch()))
+                .collect(),
+            _ => schemas,
+        }
+    }
+
+    /// The user agent that should be sent when fetching this repository, if it differs from the client's.
+    ///
+    /// Uses [`BuildRepo::user_agent`] when set, and falls back to [`BuildSource::default_user_agent`].
+    /// GitHub repos use [`crate::fetching::stable_ua`], because GitHub asks for a meaningful, unchanging user agent.
+    pub fn effective_user_agent(&self) -> Option<String> {
+        self.user_agent
+            .clone()
+            .or_else(|| self.repo_type.default_user_agent())
+    }
+
+    /// Parses the repo's URL, returning an error instead of panicking if it is invalid.
+    ///
+    /// ```
+    /// use blrs::fetching::build_repository::DEFAULT_REPOS;
+    ///
+    /// assert![DEFAULT_REPOS[0].try_url().is_ok()];
+    /// let mut repo = DEFAULT_REPOS[0].clone();
+    /// repo.url = "not a url".to_string();
+    /// assert![repo.try_url().is_err()];
+    /// ```
+    pub fn try_url(&self) -> Result<url::Url, url::ParseError> {
+        url::Url::parse(&self.url)
+    }
+
     /// Turns the link into a Url.
     ///
     /// If the `reqwest` feature is enabled (which it should be for most uses), this will parse the link into a valid `Url`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the URL is invalid, which can happen with hand-edited configs. This is only safe on
+    /// repos that were checked with [`crate::BLRSConfig::validate`], otherwise use [`BuildRepo::try_url`].
     #[cfg(feature = "reqwest")]
     #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
     pub fn url(&self) -> Url {
@@ -87,83 +140,139 @@ pub static DEFAULT_REPOS: LazyLock<[BuildRepo; 3]> = LazyLock::new(|| {
             url: "https://builder.blender.org/download/daily/?format=json&v=1".to_string(),
             nickname: "daily".to_string(),
             repo_type: RepoType::Blender,
+            user_agent: None,
+            branch_filter: None,
         },
         BuildRepo {
             repo_id: "builder.blender.org.experimental".to_string(),
             url: "https://builder.blender.org/download/experimental/?format=json&v=1".to_string(),
             nickname: "experimental".to_string(),
             repo_type: RepoType::Blender,
+            user_agent: None,
+            branch_filter: None,
         },
         BuildRepo {
             repo_id: "builder.blender.org.patch".to_string(),
             url: "https://builder.blender.org/download/patch/?format=json&v=1".to_string(),
             nickname: "patch".to_string(),
             repo_type: RepoType::Blender,
+            user_agent: None,
+            branch_filter: None,
         },
     ]
 });
 
 /// Errors that can occur when fetching data from a repository.
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum FetchError {
     /// An HTTP return code that indicates an error.
     #[cfg(feature = "reqwest")]
+    #[error("The server returned {0}")]
     ReturnCode(StatusCode, Option<&'static str>),
+    /// The server refused the request because the rate limit was exhausted.
+    /// Requests should not be retried until `reset_at`.
+    #[error("The rate limit is exhausted until {reset_at}")]
+    RateLimited {
+        /// The time at which the rate limit resets.
+        reset_at: DateTime<Utc>,
+    },
     /// An error returned by the `reqwest` library.
     #[cfg(feature = "reqwest")]
-    Reqwest(reqwest::Error),
+    #[error("Request failed: {0}")]
+    Reqwest(#[source] reqwest::Error),
     /// An invalid response from the server.
+    #[error("The server returned an invalid response")]
     InvalidResponse,
     /// Failed to deserialize the response into readable format.
-    FailedToDeserialize(serde_json::Error),
+    #[error("Failed to deserialize the response: {0}")]
+    FailedToDeserialize(#[source] serde_json::Error),
     /// There was an IO error when fetching.
-    IoError(std::io::Error),
+    #[error("IO error while fetching: {0}")]
+    IoError(#[source] std::io::Error),
+    /// The build's file name could not be parsed into build information.
+    #[error("Could not read build information from {0:?}")]
+    UnrecognizedFilename(String),
+    /// A repo's or build's URL could not be parsed.
+    #[error("Invalid url {0:?}: {1}")]
+    InvalidUrl(String, #[source] url::ParseError),
+}
+
+/// Checks whether a failed response was caused by an exhausted rate limit, as reported by GitHub.
+///
+/// Returns the time the limit resets, parsed from the `X-RateLimit-Reset` header,
+/// if the status is 403 or 429 and `X-RateLimit-Remaining` is 0.
+///
+/// ```
+/// use blrs::fetching::build_repository::rate_limit_reset;
+/// use reqwest::{header::{HeaderMap, HeaderValue}, StatusCode};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+/// headers.insert("x-ratelimit-reset", HeaderValue::from_static("1730000000"));
+///
+/// let reset = rate_limit_reset(StatusCode::FORBIDDEN, &headers).unwrap();
+/// assert_eq![reset.timestamp(), 1730000000];
+/// assert![rate_limit_reset(StatusCode::NOT_FOUND, &headers).is_none()];
+/// ```
+#[cfg(feature = "reqwest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+pub fn rate_limit_reset(status: StatusCode, headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    if !matches![
+        status,
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+    ] {
+        return None;
+    }
+
+    let header = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<i64>().ok();
+
+    match (header("x-ratelimit-remaining"), header("x-ratelimit-reset")) {
+        (Some(0), Some(reset)) => DateTime::from_timestamp(reset, 0),
+        _ => None,
+    }
 }
 
 #[cfg(feature = "reqwest")]
 #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
 /// Fetches data from a build repository using the provided client.
+///
+/// The repo's [`RepoType`] does the fetching, see [`BuildSource::fetch`].
+///
+/// The repo's [`BuildRepo::effective_user_agent`] overrides the client's user agent when present,
+/// and only branches allowed by its [`BuildRepo::branch_filter`] are returned.
 pub async fn fetch_repo(
     client: Client,
     repo: BuildRepo,
 ) -> Result<Vec<BlenderBuildSchema>, FetchError> {
-    use super::fetcher::FetcherState;
-    let url = repo.url();
-
-    debug!["Using client {:?}", client];
-
-    let mut state = FetcherState::new(client, url);
+    fetch_repo_with(client, repo, false).await
+}
 
-    loop {
-        state = state.advance().await;
+#[cfg(feature = "reqwest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+/// Like [`fetch_repo`], but every schema keeps the JSON the server returned for it in
+/// [`BlenderBuildSchema::raw`], for showing a build's raw metadata when diagnosing parsing issues.
+///
+/// This holds on to a copy of every entry, so it uses noticeably more memory than [`fetch_repo`].
+pub async fn fetch_repo_raw(
+    client: Client,
+    repo: BuildRepo,
+) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+    fetch_repo_with(client, repo, true).await
+}
 
-        match &state {
-            FetcherState::Downloading {
-                response: _,
-                downloaded_bytes: _,
-                total_bytes: _,
-            } => {}
-            _ => break,
-        }
-    }
+#[cfg(feature = "reqwest")]
+async fn fetch_repo_with(
+    client: Client,
+    repo: BuildRepo,
+    keep_raw: bool,
+) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+    let url = repo
+        .try_url()
+        .map_err(|e| FetchError::InvalidUrl(repo.url.clone(), e))?;
+    let user_agent = repo.effective_user_agent();
 
-    match state {
-        FetcherState::Downloading {
-            response: _,
-            downloaded_bytes: _,
-            total_bytes: _,
-        }
-        | FetcherState::Ready(_, _) => unreachable!(),
-        FetcherState::Finished { response, bytes } => {
-            if !response.status().is_success() {
-                return Err(FetchError::ReturnCode(
-                    response.status(),
-                    response.status().canonical_reason(),
-                ));
-            }
-            let bytes = bytes.read();
-            repo.repo_type.try_serialize(bytes.clone())
-        }
-        FetcherState::Err(e) => Err(FetchError::Reqwest(e)),
-    }
+    repo.repo_type
+        .fetch(client, url, user_agent, keep_raw)
+        .await
+        .map(|schemas| repo.filter_branches(schemas))
 }
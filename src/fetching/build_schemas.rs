@@ -1,4 +1,5 @@
 mod builder_schema;
-// pub mod github;
+/// Schemas for GitHub release feeds.
+pub mod github;
 
-pub use builder_schema::BlenderBuildSchema;
+pub use builder_schema::{BlenderBuildSchema, RawMetadata};
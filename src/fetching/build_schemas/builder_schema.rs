@@ -1,14 +1,55 @@
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
 
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
+use log::warn;
 use semver::{BuildMetadata, Prerelease, Version};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    info::{parse_blender_ver, VerboseVersion},
+    build_targets::{is_build_extension, BuildArtifactFormat},
+    info::{normalize_branch, parse_blender_ver, VerboseVersion},
     BasicBuildInfo, RemoteBuild,
 };
 
+/// The JSON a server returned for a build, kept for debugging when fetching with `keep_raw`.
+///
+/// See [`crate::fetching::build_repository::fetch_repo_raw`].
+/// It never takes part in equality, ordering or hashing, so a build is the same whether or not its raw
+/// metadata was kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RawMetadata(pub serde_json::Value);
+
+impl PartialEq for RawMetadata {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for RawMetadata {}
+
+impl PartialOrd for RawMetadata {
+    fn partial_cmp(&self, _: &Self) -> Option<std::cmp::Ordering> {
+        Some(std::cmp::Ordering::Equal)
+    }
+}
+
+impl Hash for RawMetadata {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl Deref for RawMetadata {
+    type Target = serde_json::Value;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Hash)]
 /// Represents the schema of a Blender build. This is used in fetching builds from the official builder repos.
 pub struct BlenderBuildSchema {
@@ -21,10 +62,10 @@ pub struct BlenderBuildSchema {
     /// The version string of the Blender build.
     pub version: String,
 
-    /// The Git branch this build was created from.
+    /// The Git branch this build was created from, as the repo spells it. See [`BlenderBuildSchema::branch`].
     pub branch: String,
 
-    /// Optional patch version information.
+    /// The name of the patch this build was made from, if it is a patch build (e.g., "PR109522").
     pub patch: Option<String>,
 
     /// The commit hash associated with this build.
@@ -50,29 +91,109 @@ pub struct BlenderBuildSchema {
 
     /// The release cycle of the build (e.g., "stable", "alpha").
     pub release_cycle: String, // stable,alpha,etc.
+
+    /// The JSON entry this schema was parsed from, when it was fetched with `keep_raw`.
+    /// It is not written to repo caches.
+    #[serde(skip)]
+    pub raw: Option<RawMetadata>,
 }
 
 impl From<BlenderBuildSchema> for RemoteBuild {
     fn from(val: BlenderBuildSchema) -> Self {
+        let patch_name = val.patch_name().map(str::to_string);
+        let file_mtime = val.file_mtime_dt();
+        let raw_branch = (val.branch() != val.branch).then(|| val.branch.clone());
         RemoteBuild {
             link: val.url.clone(),
-            basic: BasicBuildInfo {
-                ver: VerboseVersion::from(val.full_version()),
-                commit_dt: DateTime::from_timestamp(val.file_mtime as i64, 0).unwrap(),
-            },
+            basic: val.basic_build_info(),
             platform: Some(val.platform),
             architecture: Some(val.architecture),
             file_extension: Some(val.file_extension),
+            file_size: Some(val.file_size as u64),
+            file_mtime,
+            patch_name,
+            raw_branch,
+            raw: val.raw,
         }
     }
 }
 
+impl From<&BlenderBuildSchema> for BasicBuildInfo {
+    fn from(val: &BlenderBuildSchema) -> Self {
+        val.basic_build_info()
+    }
+}
+
 impl BlenderBuildSchema {
+    /// The time the build's file was last modified, if it can be represented.
+    pub fn file_mtime_dt(&self) -> Option<DateTime<Utc>> {
+        i64::try_from(self.file_mtime)
+            .ok()
+            .and_then(|t| DateTime::from_timestamp(t, 0))
+    }
+
+    /// The time the build was made, used as its commit time.
+    ///
+    /// The builder does not report when the build's commit was made, so the file's modification time is the
+    /// closest available timestamp. It is also kept separately in [`RemoteBuild::file_mtime`].
+    /// Timestamps that cannot be represented fall back to the unix epoch, and a warning is logged.
+    pub fn commit_dt(&self) -> DateTime<Utc> {
+        self.file_mtime_dt().unwrap_or_else(|| {
+            warn![
+                "{} has an out of range file_mtime ({}), falling back to the unix epoch",
+                self.file_name, self.file_mtime
+            ];
+            DateTime::UNIX_EPOCH
+        })
+    }
+
+    /// Builds the [`BasicBuildInfo`] of this schema, without the rest of a [`RemoteBuild`].
+    pub fn basic_build_info(&self) -> BasicBuildInfo {
+        BasicBuildInfo {
+            ver: VerboseVersion::from(self.full_version()),
+            commit_dt: self.commit_dt(),
+        }
+    }
+
+    /// The branch's canonical name, such as `stable` for `v4.2-release`, or the raw branch if it
+    /// isn't a well-known one. This is the branch used in the build's version.
+    ///
+    /// Patch builds are listed under the branch they were made against (usually `main`), so they
+    /// are always given the `patch` branch instead. See [`crate::info::normalize_branch`].
+    pub fn branch(&self) -> &str {
+        match self.is_patch() {
+            true => "patch",
+            false => normalize_branch(&self.branch).unwrap_or(&self.branch),
+        }
+    }
+
+    /// The name of the patch this build was made from, ignoring empty values.
+    pub fn patch_name(&self) -> Option<&str> {
+        self.patch.as_deref().filter(|p| !p.is_empty())
+    }
+
+    /// Whether this is a patch build, made from a pull request rather than a regular branch.
+    pub fn is_patch(&self) -> bool {
+        self.patch_name().is_some()
+    }
+
+    /// Whether this entry is an actual build, and not a sidecar file like a `.sha256` checksum.
+    ///
+    /// See [`crate::build_targets::is_build_extension`].
+    pub fn is_downloadable_build(&self) -> bool {
+        is_build_extension(&self.file_extension)
+    }
+
+    /// The format of the build's file, if its extension is recognized.
+    pub fn format(&self) -> Option<BuildArtifactFormat> {
+        BuildArtifactFormat::from_extension(&self.file_extension)
+    }
+
     /// Constructs a `Version` object from the build schema's information.
     pub fn full_version(&self) -> Version {
         Version {
             pre: Prerelease::new(&self.release_cycle).unwrap(),
-            build: BuildMetadata::new(&format!["{}.{}", self.branch, self.hash]).unwrap(),
+            build: BuildMetadata::new(&format!["{}.{}", self.branch(), self.hash]).unwrap(),
             ..parse_blender_ver(&self.version, false).unwrap()
         }
     }
@@ -81,8 +202,73 @@ impl BlenderBuildSchema {
     pub fn full_version_and_platform(&self) -> Version {
         Version {
             pre: Prerelease::new(&format!["{}-{}", self.platform, self.release_cycle]).unwrap(),
-            build: BuildMetadata::new(&format!["{}.{}", self.branch, self.hash]).unwrap(),
+            build: BuildMetadata::new(&format!["{}.{}", self.branch(), self.hash]).unwrap(),
             ..parse_blender_ver(&self.version, false).unwrap()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use chrono::DateTime;
+
+    use crate::RemoteBuild;
+
+    use super::BlenderBuildSchema;
+
+    const PATCH_ENTRY: &str = r#"{
+        "app": "Blender",
+        "url": "https://cdn.builder.blender.org/download/patch/blender-4.1.0-alpha+main-PR109522.8a6aebd9b3d4-linux.x86_64-release.tar.xz",
+        "version": "4.1.0",
+        "branch": "main",
+        "patch": "PR109522",
+        "hash": "8a6aebd9b3d4",
+        "platform": "linux",
+        "architecture": "x86_64",
+        "file_mtime": 1701263519,
+        "file_name": "blender-4.1.0-alpha+main-PR109522.8a6aebd9b3d4-linux.x86_64-release",
+        "file_size": 320145292,
+        "file_extension": "tar.xz",
+        "release_cycle": "alpha"
+    }"#;
+
+    #[test]
+    fn test_patch_build() {
+        let schema: BlenderBuildSchema = serde_json::from_str(PATCH_ENTRY).unwrap();
+        assert![schema.is_patch()];
+
+        let build = RemoteBuild::from(schema.clone());
+        assert_eq![build.patch_name.as_deref(), Some("PR109522")];
+        assert_eq![build.patch_label().as_deref(), Some("PR 109522")];
+        assert_eq![build.basic.ver.branch(), "patch"];
+        assert_eq![build.raw_branch.as_deref(), Some("main")];
+        assert_eq![build.basic.full_version(), schema.full_version()];
+        assert_eq![build.human_size().as_deref(), Some("320 MB")];
+
+        let parsed =
+            RemoteBuild::from_filename(build.link.clone(), build.link.rsplit('/').next().unwrap())
+                .unwrap();
+        assert_eq![parsed.basic.ver, build.basic.ver];
+        assert_eq![parsed.patch_name, build.patch_name];
+        assert_eq![parsed.raw_branch, build.raw_branch];
+
+        let set: HashSet<RemoteBuild> = [build, RemoteBuild::from(schema)].into();
+        assert_eq![set.len(), 1];
+    }
+
+    #[test]
+    fn test_out_of_range_mtime() {
+        let schema: BlenderBuildSchema = serde_json::from_str(PATCH_ENTRY).unwrap();
+
+        for file_mtime in [usize::MAX, 0] {
+            let build = RemoteBuild::from(BlenderBuildSchema {
+                file_mtime,
+                ..schema.clone()
+            });
+            assert_eq![build.basic.commit_dt, DateTime::UNIX_EPOCH];
+            assert_eq![build.file_mtime.is_some(), file_mtime == 0];
+        }
+    }
+}
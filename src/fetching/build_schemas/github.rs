@@ -5,14 +5,19 @@ use chrono::DateTime;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
-use crate::info::parse_blender_ver;
+use crate::{
+    build_targets::BuildArtifactFormat,
+    info::{parse_blender_ver, NULL_HASH},
+};
 
 use super::builder_schema::BlenderBuildSchema;
 
-/// ! This assumes the tag name is SemVer Compatible
+// ! This assumes the tag name is SemVer Compatible
 
+/// A page of releases as returned by the GitHub releases API.
 pub type GithubReleases = Vec<GithubRelease>;
 
+/// A single release from the GitHub releases API.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GithubRelease {
     url: String,
@@ -28,6 +33,7 @@ pub struct GithubRelease {
 }
 
 impl GithubRelease {
+    /// Converts every asset of the release into a [`BlenderBuildSchema`].
     pub fn to_build_schemas(self) -> Vec<BlenderBuildSchema> {
         let version = parse_blender_ver(&self.tag_name, false)
             .ok_or(())
@@ -43,15 +49,24 @@ impl GithubRelease {
             .into_iter()
             .map(|asset| {
                 let filename = PathBuf::from(asset.browser_download_url.split("/").last().unwrap());
-                let stem = filename.file_stem().unwrap().to_str().unwrap().to_string();
-                let extension = {
-                    filename
-                        .clone()
-                        .extension()
-                        .unwrap_or(filename.clone().file_stem().unwrap())
-                        .to_str()
-                        .unwrap()
-                        .to_string()
+                let name = filename.to_string_lossy();
+                let (stem, extension) = match BuildArtifactFormat::from_filename(&name) {
+                    Some(format) => {
+                        let ext = format.extension();
+                        (
+                            name[..name.len() - ext.len() - 1].to_string(),
+                            ext.to_string(),
+                        )
+                    }
+                    None => (
+                        filename.file_stem().unwrap().to_str().unwrap().to_string(),
+                        filename
+                            .extension()
+                            .unwrap_or(filename.file_stem().unwrap())
+                            .to_str()
+                            .unwrap()
+                            .to_string(),
+                    ),
                 };
                 let dt = DateTime::parse_from_rfc3339(&asset.updated_at)
                     .unwrap()
@@ -74,7 +89,7 @@ impl GithubRelease {
                     version: version.to_string(),
                     branch: branch.clone(),
                     patch: None,
-                    hash: "ffffffff".to_string(),
+                    hash: NULL_HASH.to_string(),
                     platform: platform.to_string(),
                     architecture: "unknown_arch".to_string(),
                     file_mtime: dt.timestamp() as usize,
@@ -82,12 +97,14 @@ impl GithubRelease {
                     file_size: asset.size,
                     file_extension: extension,
                     release_cycle: branch.clone(),
+                    raw: None,
                 }
             })
             .collect()
     }
 }
 
+/// A downloadable asset attached to a [`GithubRelease`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GithubReleaseAsset {
     url: String,
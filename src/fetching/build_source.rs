@@ -0,0 +1,315 @@
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+#[cfg(feature = "reqwest")]
+use std::future::Future;
+
+#[cfg(feature = "reqwest")]
+use reqwest::{Client, RequestBuilder, Response, Url};
+
+use super::{
+    build_repository::{FetchError, RepoType},
+    build_schemas::{
+        github::{GithubRelease, GithubReleases},
+        BlenderBuildSchema, RawMetadata,
+    },
+    stable_ua,
+};
+
+/// A kind of server that lists builds, like the Blender builder or the GitHub releases API.
+///
+/// Adding a new kind of repository only needs a new implementation of this trait, and a variant in
+/// [`RepoType`] that delegates to it.
+pub trait BuildSource {
+    /// Deserializes a single response into a list of [`BlenderBuildSchema`].
+    ///
+    /// Returns an error if the response is not valid UTF-8, or does not match the source's schema.
+    fn try_serialize(&self, data: Vec<u8>) -> Result<Vec<BlenderBuildSchema>, FetchError>;
+
+    /// Like [`BuildSource::try_serialize`], but keeps the JSON each schema was parsed from in
+    /// [`BlenderBuildSchema::raw`]. Sources that don't implement this parse without it.
+    fn try_serialize_raw(&self, data: Vec<u8>) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+        self.try_serialize(data)
+    }
+
+    /// Whether responses from this source may be split across several pages.
+    fn is_paginated(&self) -> bool {
+        false
+    }
+
+    /// The user agent to send to this source when the repo doesn't set one.
+    fn default_user_agent(&self) -> Option<String> {
+        None
+    }
+
+    /// Deserializes every page of a (possibly paginated) response and joins the results.
+    ///
+    /// With `keep_raw`, pages are parsed with [`BuildSource::try_serialize_raw`].
+    fn try_serialize_pages(
+        &self,
+        pages: Vec<Vec<u8>>,
+        keep_raw: bool,
+    ) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+        let mut schemas = vec![];
+        for page in pages {
+            schemas.extend(match keep_raw {
+                true => self.try_serialize_raw(page)?,
+                false => self.try_serialize(page)?,
+            });
+        }
+
+        Ok(schemas)
+    }
+
+    /// Fetches and deserializes the builds listed at `url`.
+    ///
+    /// If the source is paginated (see [`BuildSource::is_paginated`]), every page linked by a
+    /// `Link: rel="next"` header is followed and accumulated before deserializing.
+    /// `user_agent` overrides the client's user agent when present.
+    /// With `keep_raw`, each schema keeps the JSON it was parsed from, see [`BuildSource::try_serialize_raw`].
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+    fn fetch(
+        &self,
+        client: Client,
+        url: Url,
+        user_agent: Option<String>,
+        keep_raw: bool,
+    ) -> impl Future<Output = Result<Vec<BlenderBuildSchema>, FetchError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            debug!["Using client {:?}", client];
+
+            let mut pages = vec![];
+            let mut next = Some(url);
+
+            while let Some(url) = next.take() {
+                let mut request = client.get(url);
+                if let Some(ua) = &user_agent {
+                    request = request.header(reqwest::header::USER_AGENT, ua);
+                }
+
+                let (response, bytes) = fetch_page(request).await?;
+                if self.is_paginated() {
+                    next = super::build_repository::next_page_link(response.headers());
+                }
+                pages.push(bytes);
+            }
+
+            self.try_serialize_pages(pages, keep_raw)
+        }
+    }
+}
+
+/// The Blender builder API, which lists every build as a single JSON array.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlenderSource;
+
+impl BuildSource for BlenderSource {
+    fn try_serialize(&self, data: Vec<u8>) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+        parse_json(data)
+    }
+
+    fn try_serialize_raw(&self, data: Vec<u8>) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+        parse_json::<Vec<Value>>(data)?
+            .into_iter()
+            .map(|value| {
+                Ok(BlenderBuildSchema {
+                    raw: Some(RawMetadata(value.clone())),
+                    ..serde_json::from_value(value).map_err(FetchError::FailedToDeserialize)?
+                })
+            })
+            .collect()
+    }
+}
+
+/// The GitHub releases API. Each page holds a list of releases, and each release is converted into
+/// a list of [`BlenderBuildSchema`] using [`crate::fetching::build_schemas::github::GithubRelease::to_build_schemas`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GithubSource;
+
+impl BuildSource for GithubSource {
+    fn try_serialize(&self, data: Vec<u8>) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+        Ok(parse_json::<GithubReleases>(data)?
+            .into_iter()
+            .flat_map(|release| release.to_build_schemas())
+            .collect())
+    }
+
+    /// Every schema keeps the JSON of the release its asset belongs to.
+    fn try_serialize_raw(&self, data: Vec<u8>) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+        let mut schemas = vec![];
+        for value in parse_json::<Vec<Value>>(data)? {
+            let release: GithubRelease =
+                serde_json::from_value(value.clone()).map_err(FetchError::FailedToDeserialize)?;
+            schemas.extend(release.to_build_schemas().into_iter().map(|schema| {
+                BlenderBuildSchema {
+                    raw: Some(RawMetadata(value.clone())),
+                    ..schema
+                }
+            }));
+        }
+
+        Ok(schemas)
+    }
+
+    fn is_paginated(&self) -> bool {
+        true
+    }
+
+    /// GitHub asks for a meaningful, unchanging user agent, so this is [`stable_ua`].
+    fn default_user_agent(&self) -> Option<String> {
+        Some(stable_ua())
+    }
+}
+
+impl BuildSource for RepoType {
+    fn try_serialize(&self, data: Vec<u8>) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+        match self {
+            RepoType::Blender => BlenderSource.try_serialize(data),
+            RepoType::GithubAPI => GithubSource.try_serialize(data),
+        }
+    }
+
+    fn try_serialize_raw(&self, data: Vec<u8>) -> Result<Vec<BlenderBuildSchema>, FetchError> {
+        match self {
+            RepoType::Blender => BlenderSource.try_serialize_raw(data),
+            RepoType::GithubAPI => GithubSource.try_serialize_raw(data),
+        }
+    }
+
+    fn is_paginated(&self) -> bool {
+        match self {
+            RepoType::Blender => BlenderSource.is_paginated(),
+            RepoType::GithubAPI => GithubSource.is_paginated(),
+        }
+    }
+
+    fn default_user_agent(&self) -> Option<String> {
+        match self {
+            RepoType::Blender => BlenderSource.default_user_agent(),
+            RepoType::GithubAPI => GithubSource.default_user_agent(),
+        }
+    }
+}
+
+fn parse_json<T: DeserializeOwned>(data: Vec<u8>) -> Result<T, FetchError> {
+    let s = String::from_utf8(data).map_err(|_| FetchError::InvalidResponse)?;
+    serde_json::from_str(&s).map_err(|e| {
+        debug!["failed to parse string: {:?}", s];
+
+        FetchError::FailedToDeserialize(e)
+    })
+}
+
+/// Downloads a single page, returning the finished response and its body.
+#[cfg(feature = "reqwest")]
+async fn fetch_page(request: RequestBuilder) -> Result<(Response, Vec<u8>), FetchError> {
+    use super::{build_repository::rate_limit_reset, fetcher::FetcherState};
+
+    let mut state = FetcherState::from_request(request);
+
+    loop {
+        state = state.advance().await;
+
+        match &state {
+            FetcherState::Downloading {
+                response: _,
+                downloaded_bytes: _,
+                total_bytes: _,
+            } => {}
+            _ => break,
+        }
+    }
+
+    match state {
+        FetcherState::Downloading {
+            response: _,
+            downloaded_bytes: _,
+            total_bytes: _,
+        }
+        | FetcherState::Ready(_, _)
+        | FetcherState::Request(_) => unreachable!(),
+        FetcherState::Finished { response, bytes } => {
+            if !response.status().is_success() {
+                if let Some(reset_at) = rate_limit_reset(response.status(), response.headers()) {
+                    return Err(FetchError::RateLimited { reset_at });
+                }
+                return Err(FetchError::ReturnCode(
+                    response.status(),
+                    response.status().canonical_reason(),
+                ));
+            }
+            let bytes = bytes.read().clone();
+            Ok((response, bytes))
+        }
+        FetcherState::Err(e) => Err(FetchError::Reqwest(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fetching::build_repository::{FetchError, RepoType},
+        RemoteBuild,
+    };
+
+    use super::{BlenderSource, BuildSource, GithubSource};
+
+    #[test]
+    fn test_sources() {
+        assert![BlenderSource
+            .try_serialize(b"[]".to_vec())
+            .unwrap()
+            .is_empty()];
+        assert![GithubSource
+            .try_serialize(b"[]".to_vec())
+            .unwrap()
+            .is_empty()];
+        assert![matches![
+            BlenderSource.try_serialize(vec![0xff, 0xfe]),
+            Err(FetchError::InvalidResponse)
+        ]];
+        assert![matches![
+            GithubSource.try_serialize(b"{}".to_vec()),
+            Err(FetchError::FailedToDeserialize(_))
+        ]];
+
+        let entry = serde_json::json!({
+            "app": "Blender",
+            "url": "https://builder.blender.org/download/daily/blender-4.3.0-alpha+main.ddc9f92777cd-linux.x86_64-release.tar.xz",
+            "version": "4.3.0",
+            "branch": "main",
+            "patch": null,
+            "hash": "ddc9f92777cd",
+            "platform": "linux",
+            "architecture": "x86_64",
+            "file_mtime": 1701263519,
+            "file_name": "blender-4.3.0-alpha+main.ddc9f92777cd-linux.x86_64-release",
+            "file_size": 1,
+            "file_extension": "tar.xz",
+            "release_cycle": "alpha",
+            "unknown_field": 42
+        });
+        let data = serde_json::to_vec(&[&entry]).unwrap();
+        assert![BlenderSource.try_serialize(data.clone()).unwrap()[0]
+            .raw
+            .is_none()];
+        let schemas = RepoType::Blender
+            .try_serialize_pages(vec![data], true)
+            .unwrap();
+        assert_eq![schemas[0].raw.as_deref(), Some(&entry)];
+        assert_eq![
+            RemoteBuild::from(schemas[0].clone()).raw.unwrap()["unknown_field"],
+            42
+        ];
+
+        assert![!RepoType::Blender.is_paginated()];
+        assert![RepoType::GithubAPI.is_paginated()];
+        assert![RepoType::Blender.default_user_agent().is_none()];
+        assert![RepoType::GithubAPI.default_user_agent().is_some()];
+    }
+}
@@ -4,6 +4,9 @@ use hex::ToHex;
 use log::debug;
 use semver::Version;
 use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::build_targets::BuildArtifactFormat;
 
 use super::build_schemas::BlenderBuildSchema;
 
@@ -21,10 +24,14 @@ pub fn get_sha256_pairs(lst: Vec<BlenderBuildSchema>) -> HashMap<Version, Sha256
     let mut map: HashMap<Version, Sha256Pair> = HashMap::new();
 
     for schema in lst {
+        let is_checksum = schema.format() == Some(BuildArtifactFormat::Sha256);
+        if !is_checksum && !schema.is_downloadable_build() {
+            continue;
+        }
         let ver = schema.full_version_and_platform();
 
         let entry = map.remove(&ver);
-        if schema.file_extension == "sha256" {
+        if is_checksum {
             map.insert(
                 ver,
                 Sha256Pair {
@@ -53,23 +60,14 @@ pub fn get_sha256_pairs(lst: Vec<BlenderBuildSchema>) -> HashMap<Version, Sha256
 }
 
 /// Enum representing possible errors during parsing.
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum ParseError {
     /// Error encountered while converting UTF-8 encoded bytes.
-    FromUtf8(FromUtf8Error),
+    #[error("The checksum file is not valid UTF-8: {0}")]
+    FromUtf8(#[from] FromUtf8Error),
     /// I/O error occurred during file operations.
-    Io(std::io::Error),
-}
-
-impl From<std::io::Error> for ParseError {
-    fn from(value: std::io::Error) -> Self {
-        ParseError::Io(value)
-    }
-}
-impl From<FromUtf8Error> for ParseError {
-    fn from(value: FromUtf8Error) -> Self {
-        ParseError::FromUtf8(value)
-    }
+    #[error("IO error while reading checksums: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 ///  Calculates the SHA256 hash of a file.
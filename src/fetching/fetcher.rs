@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use reqwest::{Client, Response, Url};
+use reqwest::{Client, RequestBuilder, Response, Url};
 
 use parking_lot::RwLock;
 
@@ -92,6 +92,9 @@ pub enum FetcherState {
     /// Initial ready state, where the client and URL are specified.
     Ready(Client, Url),
 
+    /// Initial ready state, where a fully configured request is specified.
+    Request(RequestBuilder),
+
     /// Downloading state, where data is being fetched from the server.
     Downloading {
         /// The HTTP response object.
@@ -124,6 +127,12 @@ impl FetcherState {
         Self::Ready(client, url)
     }
 
+    /// Creates a new `FetcherState` instance from a prepared request, e.g. one with extra headers.
+    #[inline]
+    pub fn from_request(request: RequestBuilder) -> Self {
+        Self::Request(request)
+    }
+
     /// Advances the fetcher to the next state based on the current state.
     ///
     /// This method is used to manage the fetch process and handle any errors that
@@ -131,8 +140,13 @@ impl FetcherState {
     /// state.
     pub async fn advance(self) -> Self {
         match self {
-            Self::Ready(client, url) => {
-                let response = client.get(url).send().await;
+            Self::Ready(_, _) | Self::Request(_) => {
+                let request = match self {
+                    Self::Ready(client, url) => client.get(url),
+                    Self::Request(request) => request,
+                    _ => unreachable!(),
+                };
+                let response = request.send().await;
                 match response {
                     Ok(response) => Self::Downloading {
                         total_bytes: response.content_length(),
@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    future::Future,
+    io,
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use log::debug;
+use reqwest::Client;
+use tokio::task::JoinHandle;
+
+use crate::{
+    atomic::write_atomically,
+    build_targets::{filter_schemas_by_targets, BuildTarget},
+    BLRSPaths,
+};
+
+use super::{
+    build_repository::{fetch_repo, BuildRepo, FetchError},
+    build_schemas::BlenderBuildSchema,
+};
+
+/// Writes a repo cache so that readers never observe a half-written file.
+///
+/// The data is written to a temporary file next to the cache, then renamed over it.
+pub fn write_repo_cache(path: &Path, schemas: &[BlenderBuildSchema]) -> io::Result<()> {
+    let data = serde_json::to_vec(schemas)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    write_atomically(path, &data)
+}
+
+/// Fetches a repository and atomically replaces its cache file.
+///
+/// Only builds matching one of `fetch_targets` are written, usually taken from
+/// [`crate::BLRSConfig::fetch_targets`]. `None` keeps every build.
+///
+/// Returns the amount of builds that were written to the cache.
+pub async fn refresh_repo(
+    client: Client,
+    repo: BuildRepo,
+    paths: BLRSPaths,
+    fetch_targets: Option<Vec<BuildTarget>>,
+) -> Result<usize, FetchError> {
+    let cache_path = paths.path_to_repo_cache(&repo);
+    let schemas =
+        filter_schemas_by_targets(fetch_repo(client, repo).await?, fetch_targets.as_deref());
+
+    debug!["Writing {} builds to {:?}", schemas.len(), cache_path];
+    write_repo_cache(&cache_path, &schemas).map_err(FetchError::IoError)?;
+
+    Ok(schemas.len())
+}
+
+/// A handle to a repo refresh running in the background.
+///
+/// The handle can be polled with [`RefreshHandle::is_finished`], or awaited to get the result.
+/// Dropping the handle does not cancel the refresh.
+#[derive(Debug)]
+pub struct RefreshHandle {
+    handle: JoinHandle<Result<usize, FetchError>>,
+}
+
+impl RefreshHandle {
+    /// Spawns [`refresh_repo`] onto the current tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn(
+        client: Client,
+        repo: BuildRepo,
+        paths: BLRSPaths,
+        fetch_targets: Option<Vec<BuildTarget>>,
+    ) -> Self {
+        Self {
+            handle: tokio::spawn(refresh_repo(client, repo, paths, fetch_targets)),
+        }
+    }
+
+    /// Checks whether the refresh has finished, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Stops the refresh. The cache is either fully written or left untouched.
+    pub fn abort(&self) {
+        self.handle.abort()
+    }
+}
+
+impl Future for RefreshHandle {
+    type Output = Result<usize, FetchError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.handle).poll(cx).map(|r| match r {
+            Ok(r) => r,
+            Err(e) => Err(FetchError::IoError(io::Error::other(e))),
+        })
+    }
+}
@@ -1,20 +1,42 @@
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use crate::BasicBuildInfo;
+use crate::{
+    build_targets::{is_build_extension, BuildArtifactFormat},
+    fetching::build_schemas::RawMetadata,
+    info::{normalize_branch, parse_blender_ver, VerboseVersion},
+    BasicBuildInfo,
+};
+
+#[cfg(feature = "reqwest")]
+use reqwest::{Client, Url};
 
 #[cfg(feature = "reqwest")]
-use reqwest::Url;
+use super::build_repository::FetchError;
+
+static PLATFORM_ARCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(linux|windows|darwin|macos)(?:[.\-_](x86_64|x64|amd64|arm64|aarch64|i686))?")
+        .unwrap()
+});
 
 /// A struct representing a remote build.
 ///
 /// This contains information about a build retrieved from a URL,
 /// such as its basic build info and any additional platform-specific details.
-#[derive(PartialEq, PartialOrd, Debug, Clone, Serialize, Deserialize)]
+///
+/// Every field except [`RemoteBuild::raw`] takes part in equality and hashing, so the same build fetched twice
+/// is only stored once in a set.
+#[derive(PartialEq, Eq, Hash, PartialOrd, Debug, Clone, Serialize, Deserialize)]
 pub struct RemoteBuild {
     /// The URL of the build.
     pub link: String,
 
     /// The basic information of the build.
+    ///
+    /// Builds are ordered by [`BasicBuildInfo`], which compares `commit_dt` first.
     pub basic: BasicBuildInfo,
 
     /// The platform on which this build was executed (optional).
@@ -25,6 +47,33 @@ pub struct RemoteBuild {
 
     /// The file extension associated with this build (optional).
     pub file_extension: Option<String>,
+
+    /// The size of the build's file in bytes (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<u64>,
+
+    /// When the build's file was last modified on the server (optional).
+    ///
+    /// This is when the artifact was uploaded, which can differ from `basic.commit_dt`, and is not used for ordering.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_mtime: Option<DateTime<Utc>>,
+
+    /// The name of the patch this build was made from, such as `PR109522` (optional).
+    ///
+    /// Patch builds are built from pull requests, so this is usually a better label than the branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch_name: Option<String>,
+
+    /// The branch as the repo spelled it, when it differs from the canonical branch in `basic` (optional).
+    ///
+    /// See [`crate::info::normalize_branch`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_branch: Option<String>,
+
+    /// The JSON entry this build was parsed from, when it was fetched with `keep_raw`.
+    /// It is not written to repo caches, and never takes part in equality or hashing.
+    #[serde(skip)]
+    pub raw: Option<RawMetadata>,
 }
 
 impl std::fmt::Display for RemoteBuild {
@@ -49,15 +98,270 @@ impl std::fmt::Display for RemoteBuild {
     }
 }
 
+/// Reads the `Content-Length` header directly.
+///
+/// `Response::content_length` reports the size of the body, which is always 0 for HEAD requests.
+#[cfg(feature = "reqwest")]
+fn content_length(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// Splits a patch name like `PR109522` off the end of a branch such as `main-PR109522`.
+fn split_patch(branch: &str) -> (&str, Option<&str>) {
+    match branch.rsplit_once('-') {
+        Some((base, patch))
+            if patch
+                .strip_prefix("PR")
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())) =>
+        {
+            (base, Some(patch))
+        }
+        _ => (branch, None),
+    }
+}
+
+impl AsRef<BasicBuildInfo> for RemoteBuild {
+    fn as_ref(&self) -> &BasicBuildInfo {
+        &self.basic
+    }
+}
+
 impl RemoteBuild {
+    /// Whether this is an actual build, and not a sidecar file like a `.sha256` checksum.
+    ///
+    /// Builds with an unknown extension are assumed to be downloadable.
+    /// See [`crate::build_targets::is_build_extension`].
+    pub fn is_downloadable_build(&self) -> bool {
+        self.file_extension
+            .as_deref()
+            .is_none_or(is_build_extension)
+    }
+
+    /// The format of the build's file, if its extension is recognized.
+    pub fn format(&self) -> Option<BuildArtifactFormat> {
+        self.file_extension
+            .as_deref()
+            .and_then(BuildArtifactFormat::from_extension)
+    }
+
+    /// The size of the build's file in a readable form, such as `320 MB`, using decimal units.
+    ///
+    /// Returns `None` if the size is not known, see [`RemoteBuild::file_size`].
+    pub fn human_size(&self) -> Option<String> {
+        const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+        let mut size = self.file_size? as f64;
+        let mut unit = 0;
+        while size >= 1000.0 && unit < UNITS.len() - 1 {
+            size /= 1000.0;
+            unit += 1;
+        }
+
+        Some(match unit == 0 || size >= 10.0 {
+            true => format!["{:.0} {}", size, UNITS[unit]],
+            false => format!["{:.1} {}", size, UNITS[unit]],
+        })
+    }
+
+    /// Whether this build was made from a patch rather than a regular branch.
+    pub fn is_patch(&self) -> bool {
+        self.patch_name.is_some()
+    }
+
+    /// A label for the patch this build was made from, such as `PR 109522`.
+    ///
+    /// Pull request patches are given a space between `PR` and their number,
+    /// other patch names are returned as they are.
+    ///
+    /// ```
+    /// use blrs::RemoteBuild;
+    ///
+    /// let name = "blender-4.1.0-alpha+main.e6a3b5ed3a56-linux.x86_64-release.tar.xz";
+    /// let mut build = RemoteBuild::from_filename(format!["https://example.com/{name}"], name).unwrap();
+    /// assert_eq![build.patch_label(), None];
+    ///
+    /// build.patch_name = Some("PR109522".to_string());
+    /// assert_eq![build.patch_label().as_deref(), Some("PR 109522")];
+    /// ```
+    pub fn patch_label(&self) -> Option<String> {
+        self.patch_name.as_ref().map(|name| {
+            match name
+                .strip_prefix("PR")
+                .filter(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            {
+                Some(number) => format!["PR {}", number],
+                None => name.clone(),
+            }
+        })
+    }
+
+    /// Constructs a `RemoteBuild` from a link and the name of the file it points to.
+    ///
+    /// The version, branch, and hash are parsed from the file name, along with the platform, architecture,
+    /// and extension when they can be found. Returns `None` if no version could be parsed.
+    /// The branch is normalized like the builder's feeds are, and patch builds such as `+main-PR109522`
+    /// are given the `patch` branch and their patch name, so both name the same build the same way.
+    /// Since file names have no dates, the commit time is the unix epoch, like
+    /// [`crate::fetching::build_schemas::BlenderBuildSchema::commit_dt`] falls back to, so parsing the same
+    /// name twice gives equal builds. Set it from a better source when there is one.
+    ///
+    /// ```
+    /// use blrs::RemoteBuild;
+    ///
+    /// let name = "blender-4.3.0-alpha+main.ddc9f92777cd-linux.x86_64-release.tar.xz";
+    /// let build = RemoteBuild::from_filename(format!["https://example.com/{name}"], name).unwrap();
+    ///
+    /// assert_eq![build.basic.ver.to_string(), "4.3.0-alpha+daily.ddc9f92777cd"];
+    /// assert_eq![build.raw_branch.as_deref(), Some("main")];
+    /// assert_eq![build.platform.as_deref(), Some("linux")];
+    /// assert_eq![build.architecture.as_deref(), Some("x86_64")];
+    /// assert_eq![build.file_extension.as_deref(), Some("tar.xz")];
+    /// assert_eq![build, RemoteBuild::from_filename(build.link.clone(), name).unwrap()];
+    /// ```
+    pub fn from_filename(link: String, filename: &str) -> Option<Self> {
+        let extension =
+            BuildArtifactFormat::from_filename(filename).map(|f| f.extension().to_string());
+        let stem = match &extension {
+            Some(ext) => &filename[..filename.len() - ext.len() - 1],
+            None => filename,
+        };
+
+        let version = VerboseVersion::from(parse_blender_ver(stem, true)?);
+        let (raw_branch, patch_name) = split_patch(version.branch());
+        let (raw_branch, patch_name) = (raw_branch.to_string(), patch_name.map(str::to_string));
+        let canonical = match patch_name {
+            Some(_) => Some("patch"),
+            None => normalize_branch(&raw_branch),
+        };
+        let (ver, raw_branch) = match canonical.filter(|c| *c != version.branch()) {
+            Some(c) => (
+                version.clone().with_branch(Some(c)).unwrap_or(version),
+                Some(raw_branch),
+            ),
+            None => (version, None),
+        };
+        let (platform, architecture) = match PLATFORM_ARCH.captures(stem) {
+            Some(c) => (
+                c.get(1).map(|m| m.as_str().to_lowercase()),
+                c.get(2).map(|m| m.as_str().to_lowercase()),
+            ),
+            None => (None, None),
+        };
+
+        Some(Self {
+            link,
+            basic: BasicBuildInfo {
+                ver,
+                commit_dt: DateTime::UNIX_EPOCH,
+            },
+            platform,
+            architecture,
+            file_extension: extension,
+            file_size: None,
+            file_mtime: None,
+            patch_name,
+            raw_branch,
+            raw: None,
+        })
+    }
+
+    /// Constructs a `RemoteBuild` from a direct download URL.
+    ///
+    /// A HEAD request is made to find the file's size (`Content-Length`) and modification date (`Last-Modified`),
+    /// which is used in place of the commit time. The rest is parsed from the file name with [`RemoteBuild::from_filename`].
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+    pub async fn from_url(client: Client, url: Url) -> Result<RemoteBuild, FetchError> {
+        let filename = url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string())
+            .ok_or_else(|| FetchError::UnrecognizedFilename(url.to_string()))?;
+
+        let mut build = Self::from_filename(url.to_string(), &filename)
+            .ok_or(FetchError::UnrecognizedFilename(filename))?;
+
+        let response = client.head(url).send().await.map_err(FetchError::Reqwest)?;
+        if !response.status().is_success() {
+            return Err(FetchError::ReturnCode(
+                response.status(),
+                response.status().canonical_reason(),
+            ));
+        }
+
+        build.file_size = content_length(response.headers());
+        if let Some(dt) = response
+            .headers()
+            .get(reqwest::header::LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())
+        {
+            build.basic.commit_dt = dt.to_utc();
+        }
+
+        Ok(build)
+    }
+
+    /// Finds the size of the build's file in bytes without downloading it.
+    ///
+    /// A HEAD request is made and the `Content-Length` header is read. If the server rejects HEAD requests,
+    /// a GET request is made instead and dropped as soon as the headers arrive.
+    /// Returns `Ok(None)` if the server does not report a size.
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+    pub async fn remote_size(&self, client: Client) -> Result<Option<u64>, FetchError> {
+        use reqwest::StatusCode;
+
+        let url = self
+            .try_url()
+            .map_err(|e| FetchError::InvalidUrl(self.link.clone(), e))?;
+        let response = client
+            .head(url.clone())
+            .send()
+            .await
+            .map_err(FetchError::Reqwest)?;
+
+        let response = match response.status() {
+            s if s.is_success() => return Ok(content_length(response.headers())),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED => {
+                client.get(url).send().await.map_err(FetchError::Reqwest)?
+            }
+            _ => response,
+        };
+
+        match response.status().is_success() {
+            true => Ok(content_length(response.headers())),
+            false => Err(FetchError::ReturnCode(
+                response.status(),
+                response.status().canonical_reason(),
+            )),
+        }
+    }
+
     /// Gets a string representation of the remote build including the link.
     pub fn string_with_link(&self) -> String {
         format!["{} - {:?}", self, self.link]
     }
 
+    /// Parses the build's link, returning an error instead of panicking if it is invalid.
+    pub fn try_url(&self) -> Result<url::Url, url::ParseError> {
+        url::Url::parse(&self.link)
+    }
+
     /// Turns the link into a Url.
     ///
     /// If the `reqwest` feature is enabled (which it should be for most uses), this will parse the link into a valid `Url`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the link is invalid, which can happen with malformed repo caches. This is only safe on
+    /// builds whose link is known to be valid, otherwise use [`RemoteBuild::try_url`].
     #[cfg(feature = "reqwest")]
     #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
     pub fn url(&self) -> Url {
@@ -1,13 +1,41 @@
+#[cfg(not(target_arch = "wasm32"))]
 mod binfo_extraction;
 mod blendfile_reader;
+#[cfg(not(target_arch = "wasm32"))]
+mod manifest;
+#[cfg(not(target_arch = "wasm32"))]
+mod probe;
+#[cfg(not(target_arch = "wasm32"))]
+mod suggestion;
+#[cfg(not(target_arch = "wasm32"))]
+mod system_builds;
 mod verbose_version;
 
 /// This module provides functionality to extract, parse, and house build-related data from Blender builds.
 pub mod build_info;
 /// Module containing basic information about Blender builds.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
 pub mod launching;
 
+#[cfg(not(target_arch = "wasm32"))]
 pub use binfo_extraction::{get_info_from_blender, CollectedInfo};
-pub use blendfile_reader::{read_blendfile_header, BlendFileHeader, CompressionType};
+pub use blendfile_reader::{
+    read_blendfile_header, BlendFileHeader, BlendReadErr, CompressionType, Endianness,
+};
 pub use build_info::{parse_blender_ver, BasicBuildInfo, LocalBuild};
-pub use verbose_version::VerboseVersion;
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub use manifest::{IntegrityReport, Manifest, ManifestEntry, MANIFEST_FILENAME};
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub use probe::{probe_build, ProbeError, ProbedBuild};
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub use suggestion::suggest_build_for_file;
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub use system_builds::{detect_system_blenders, system_blender_candidates, SYSTEM_REPO_ID};
+pub use verbose_version::{
+    hashes_match, normalize_branch, VerboseVersion, MIN_HASH_PREFIX, NULL_BRANCH, NULL_HASH,
+};
@@ -20,6 +20,16 @@ pub enum CompressionType {
     None,
 }
 
+/// The byte order a Blender file was saved with.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// Little endian, marked with `v` in the header. Used by every current platform.
+    #[default]
+    Little,
+    /// Big endian, marked with `V` in the header. Used by old PowerPC and SPARC machines.
+    Big,
+}
+
 /// The header information for a Blender file.
 ///
 /// This struct contains metadata about the file, including the version and compression type.
@@ -29,6 +39,18 @@ pub struct BlendFileHeader {
     pub version: (u8, u8),
     /// The compression type used in the file header.
     pub compression_type: CompressionType,
+    /// The file's subversion, which is bumped whenever the file format changes within a release.
+    ///
+    /// This is read from the file's global block, and is `None` if that block could not be found.
+    pub subversion: Option<u16>,
+    /// The oldest Blender version that can open this file without losing data, as `major.minor.subversion`.
+    ///
+    /// This is read from the file's global block, and is `None` if that block could not be found.
+    pub min_version: Option<Version>,
+    /// The size of a pointer in bytes on the machine that saved the file, either 4 or 8.
+    pub pointer_size: u8,
+    /// The byte order of the machine that saved the file.
+    pub endianness: Endianness,
 }
 
 impl BlendFileHeader {
@@ -38,67 +60,208 @@ impl BlendFileHeader {
     pub fn version(&self) -> Version {
         Version::new(self.version.0 as u64, self.version.1 as u64, 0)
     }
+
+    /// Returns the version stored in the file header along with its subversion, as `major.minor.subversion`.
+    pub fn full_version(&self) -> Version {
+        Version::new(
+            self.version.0 as u64,
+            self.version.1 as u64,
+            self.subversion.unwrap_or(0) as u64,
+        )
+    }
 }
 
 const BYTE_REPRESENT_ZERO: u8 = b'0';
 
-fn parse_header_version(nums: &[u8; 3]) -> (u8, u8) {
-    let major = nums[0] - BYTE_REPRESENT_ZERO;
-    let minor = nums[1] - BYTE_REPRESENT_ZERO;
-    let patch = nums[2] - BYTE_REPRESENT_ZERO;
-    (major, minor * 10 + patch)
+/// Parses the version digits of a .blend header into `(major, minor)`.
+///
+/// Legacy headers store 3 digits, one for the major version and two for the minor version, so
+/// `293` is 2.93 and `402` is 4.2. Newer headers store 4 digits, two for each, so `0500` is 5.0.
+fn parse_header_version(nums: &[u8]) -> Option<(u8, u8)> {
+    if !nums.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let number = |digits: &[u8]| {
+        digits
+            .iter()
+            .fold(0, |acc, d| acc * 10 + (d - BYTE_REPRESENT_ZERO))
+    };
+
+    match nums.len() {
+        3 => Some((number(&nums[..1]), number(&nums[1..]))),
+        4 => Some((number(&nums[..2]), number(&nums[2..]))),
+        _ => None,
+    }
 }
 
-fn read_basic_header(path: &Path) -> Result<[u8; 3], std::io::Error> {
-    let mut file = File::open(path)?;
+/// The version information stored in a file's `GLOB` block.
+struct GlobalInfo {
+    subversion: u16,
+    min_version: u16,
+    min_subversion: u16,
+}
 
-    let mut header_bytes = [0; 7];
-    file.read_exact(&mut header_bytes)?;
+/// The parts of a .blend file read by [`read_blend`].
+struct RawHeader {
+    version: (u8, u8),
+    layout: BlockLayout,
+    global: Option<GlobalInfo>,
+}
 
-    let b = &header_bytes;
-    if [b"BLENDER", b"BULLETf"].contains(&b) {
-        file.read_exact(&mut [0; 2])?;
-        let mut version_bytes = [0; 3];
-        file.read_exact(&mut version_bytes)?;
-        Ok(version_bytes)
-    } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            "The file header does not match blender's traditional header".to_string(),
-        ))
+/// How the blocks of a .blend file are laid out, as described by its header.
+#[derive(Clone, Copy)]
+struct BlockLayout {
+    pointer_size: u64,
+    big_endian: bool,
+    /// Whether blocks use the 64-bit block headers introduced alongside the newer file header.
+    large: bool,
+}
+
+/// Walks the file's blocks until the `GLOB` block is found, and reads the versions stored in it.
+///
+/// In legacy files, every block starts with a header of a 4 byte code, a 4 byte length, an old
+/// memory address the size of a pointer, and two more 4 byte integers. Files with the newer header
+/// use a 4 byte code, a 4 byte struct index, an 8 byte address, an 8 byte length, and an 8 byte count.
+/// Either way the header is followed by `length` bytes of data.
+/// `GLOB` data starts with a 4 character subversion string, then the subversion, minimum version,
+/// and minimum subversion as shorts.
+fn read_global<R: Read>(
+    reader: &mut R,
+    layout: BlockLayout,
+) -> std::io::Result<Option<GlobalInfo>> {
+    let big_endian = layout.big_endian;
+    let u16_from = |b: [u8; 2]| match big_endian {
+        true => u16::from_be_bytes(b),
+        false => u16::from_le_bytes(b),
+    };
+    let skip = |reader: &mut R, n: u64| {
+        std::io::copy(&mut reader.by_ref().take(n), &mut std::io::sink()).map(|_| ())
+    };
+
+    loop {
+        let mut code = [0; 4];
+        reader.read_exact(&mut code)?;
+        if &code == b"ENDB" {
+            return Ok(None);
+        }
+
+        let len = match layout.large {
+            true => {
+                skip(reader, 4 + 8)?;
+                let mut len = [0; 8];
+                reader.read_exact(&mut len)?;
+                skip(reader, 8)?;
+                match big_endian {
+                    true => u64::from_be_bytes(len),
+                    false => u64::from_le_bytes(len),
+                }
+            }
+            false => {
+                let mut len = [0; 4];
+                reader.read_exact(&mut len)?;
+                skip(reader, layout.pointer_size + 8)?;
+                (match big_endian {
+                    true => u32::from_be_bytes(len),
+                    false => u32::from_le_bytes(len),
+                }) as u64
+            }
+        };
+
+        if &code == b"GLOB" {
+            let mut data = [0; 10];
+            reader.read_exact(&mut data)?;
+            return Ok(Some(GlobalInfo {
+                subversion: u16_from([data[4], data[5]]),
+                min_version: u16_from([data[6], data[7]]),
+                min_subversion: u16_from([data[8], data[9]]),
+            }));
+        }
+
+        skip(reader, len)?;
     }
 }
 
+fn invalid_header() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        "The file header does not match blender's traditional header".to_string(),
+    )
+}
+
+/// Reads the header of an uncompressed .blend stream, along with its global block if it has one.
+///
+/// Legacy headers are 12 bytes: `BLENDER`, then `_` or `-` for 4 or 8 byte pointers, `v` or `V` for
+/// little or big endian, and finally 3 version digits (`BLENDER-v402`).
+/// Newer headers put the header's size after `BLENDER`, followed by `-`, a 2 digit format
+/// version, `v`, and 4 version digits (`BLENDER17-01v0500`). These always use 8 byte pointers
+/// and little endian.
+fn read_blend<R: Read>(mut reader: R) -> Result<RawHeader, std::io::Error> {
+    let mut magic = [0; 9];
+    reader.read_exact(&mut magic)?;
+    if ![b"BLENDER", b"BULLETf"].contains(&&magic[..7].try_into().unwrap()) {
+        return Err(invalid_header());
+    }
+
+    let (version, layout) = if magic[7..].iter().all(u8::is_ascii_digit) {
+        let size =
+            ((magic[7] - BYTE_REPRESENT_ZERO) * 10 + (magic[8] - BYTE_REPRESENT_ZERO)) as usize;
+        if size < 17 {
+            return Err(invalid_header());
+        }
+        let mut rest = vec![0; size - 9];
+        reader.read_exact(&mut rest)?;
+
+        let version = parse_header_version(&rest[4..8]).ok_or_else(invalid_header)?;
+        let layout = BlockLayout {
+            pointer_size: 8,
+            big_endian: false,
+            large: true,
+        };
+        (version, layout)
+    } else {
+        let mut digits = [0; 3];
+        reader.read_exact(&mut digits)?;
+
+        let version = parse_header_version(&digits).ok_or_else(invalid_header)?;
+        let layout = BlockLayout {
+            pointer_size: if magic[7] == b'-' { 8 } else { 4 },
+            big_endian: magic[8] == b'V',
+            large: false,
+        };
+        (version, layout)
+    };
+
+    let global = read_global(&mut reader, layout).ok().flatten();
+
+    Ok(RawHeader {
+        version,
+        layout,
+        global,
+    })
+}
+
+fn read_basic_header(path: &Path) -> Result<RawHeader, std::io::Error> {
+    read_blend(std::io::BufReader::new(File::open(path)?))
+}
+
 #[cfg(feature = "compressed-blends")]
-fn read_gzip_header(path: &Path) -> Result<[u8; 3], std::io::Error> {
+fn read_gzip_header(path: &Path) -> Result<RawHeader, std::io::Error> {
     use flate2::read::GzDecoder;
-    let mut file = File::open(path)?;
-    let mut decoder = GzDecoder::new(&mut file);
-    let mut header = [0; 9];
-    decoder.read_exact(&mut header)?;
-
-    let mut version_bytes = [0; 3];
-    decoder.read_exact(&mut version_bytes)?;
-    Ok(version_bytes)
+    let file = File::open(path)?;
+    read_blend(GzDecoder::new(std::io::BufReader::new(file)))
 }
 
 #[cfg(feature = "compressed-blends")]
-fn read_zstd_header(path: &Path) -> Result<[u8; 3], std::io::Error> {
+fn read_zstd_header(path: &Path) -> Result<RawHeader, std::io::Error> {
     use zstd::Decoder as zstdDecoder;
     let file = File::open(path)?;
-    let mut header = [0; 9];
-
-    let mut decoder = zstdDecoder::new(file)?;
-    decoder.read_exact(&mut header)?;
-
-    let mut version_bytes = [0; 3];
-    decoder.read_exact(&mut version_bytes)?;
-    Ok(version_bytes)
+    read_blend(zstdDecoder::new(file)?)
 }
 
-type BlendReadErr = (std::io::Error, Option<(std::io::Error, std::io::Error)>);
+/// The errors from each attempt at reading a .blend header: uncompressed, then gzip and zstd if `compressed-blends` is enabled.
+pub type BlendReadErr = (std::io::Error, Option<(std::io::Error, std::io::Error)>);
 
-fn get_blendfile_header(path: &Path) -> Result<([u8; 3], CompressionType), BlendReadErr> {
+fn get_blendfile_header(path: &Path) -> Result<(RawHeader, CompressionType), BlendReadErr> {
     let b_e = match read_basic_header(path).map(|b| (b, CompressionType::None)) {
         Ok(v) => return Ok(v),
         Err(e) => e,
@@ -123,10 +286,160 @@ fn get_blendfile_header(path: &Path) -> Result<([u8; 3], CompressionType), Blend
     }
 }
 
-/// Tries to read the first 7 bytes of a file, to check if it is a blender file.
+/// Tries to read the header of a file, to check if it is a blender file.
+///
+/// The subversion and minimum version are read from the file's global block when it can be found.
 pub fn read_blendfile_header(path: &Path) -> Result<BlendFileHeader, BlendReadErr> {
-    get_blendfile_header(path).map(|(b, c)| BlendFileHeader {
-        version: parse_header_version(&b),
+    get_blendfile_header(path).map(|(raw, c)| BlendFileHeader {
+        version: raw.version,
         compression_type: c,
+        pointer_size: raw.layout.pointer_size as u8,
+        endianness: match raw.layout.big_endian {
+            true => Endianness::Big,
+            false => Endianness::Little,
+        },
+        subversion: raw.global.as_ref().map(|g| g.subversion),
+        min_version: raw.global.map(|g| {
+            Version::new(
+                (g.min_version / 100) as u64,
+                (g.min_version % 100) as u64,
+                g.min_subversion as u64,
+            )
+        }),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::{read_blendfile_header, Endianness};
+
+    /// Builds a little endian, 8 byte pointer .blend with a `REND` block followed by a `GLOB` block.
+    fn blend_with_global(version: &[u8; 3], subversion: u16, min: (u16, u16)) -> Vec<u8> {
+        let mut data = b"BLENDER-v".to_vec();
+        data.extend(version);
+
+        let block = |code: &[u8; 4], body: &[u8]| {
+            let mut b = code.to_vec();
+            b.extend((body.len() as u32).to_le_bytes());
+            b.extend([0; 16]);
+            b.extend(body);
+            b
+        };
+        data.extend(block(b"REND", &[1, 2, 3, 4, 5]));
+
+        let mut global = format!["{:4}", subversion].into_bytes();
+        global.extend(subversion.to_le_bytes());
+        global.extend(min.0.to_le_bytes());
+        global.extend(min.1.to_le_bytes());
+        global.extend([0; 6]);
+        data.extend(block(b"GLOB", &global));
+        data.extend(block(b"ENDB", &[]));
+        data
+    }
+
+    /// Headers of files saved by known Blender versions, and the versions they should parse to.
+    const HEADER_FIXTURES: [(&[u8], (u8, u8)); 6] = [
+        (b"BLENDER_v279", (2, 79)),
+        (b"BLENDER-v293", (2, 93)),
+        (b"BLENDER-v306", (3, 6)),
+        (b"BLENDER-v402", (4, 2)),
+        (b"BLENDER-V410", (4, 10)),
+        (b"BLENDER17-01v0500", (5, 0)),
+    ];
+
+    #[test]
+    fn test_header_versions() {
+        let path = std::env::temp_dir().join(format!["blrs-test-{}.blend", uuid::Uuid::new_v4()]);
+
+        for (header, version) in HEADER_FIXTURES {
+            std::fs::write(&path, header).unwrap();
+            let parsed = read_blendfile_header(&path).unwrap();
+            assert_eq![
+                parsed.version,
+                version,
+                "{}",
+                String::from_utf8_lossy(header)
+            ];
+        }
+
+        std::fs::write(&path, b"BLENDER-v4x2").unwrap();
+        assert![read_blendfile_header(&path).is_err()];
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_pointer_size_and_endianness() {
+        let path = std::env::temp_dir().join(format!["blrs-test-{}.blend", uuid::Uuid::new_v4()]);
+
+        // A 32-bit big endian file, like one saved on a PowerPC Mac
+        let mut data = b"BLENDER_V249".to_vec();
+        data.extend(b"GLOB");
+        data.extend(10u32.to_be_bytes());
+        data.extend([0; 12]);
+        data.extend(b"   2");
+        data.extend(2u16.to_be_bytes());
+        data.extend(245u16.to_be_bytes());
+        data.extend(1u16.to_be_bytes());
+
+        std::fs::write(&path, data).unwrap();
+        let header = read_blendfile_header(&path).unwrap();
+        assert_eq![header.pointer_size, 4];
+        assert_eq![header.endianness, Endianness::Big];
+        assert_eq![header.full_version(), Version::new(2, 49, 2)];
+        assert_eq![header.min_version, Some(Version::new(2, 45, 1))];
+
+        std::fs::write(&path, b"BLENDER-v402").unwrap();
+        let header = read_blendfile_header(&path).unwrap();
+        assert_eq![header.pointer_size, 8];
+        assert_eq![header.endianness, Endianness::Little];
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_large_block_headers() {
+        let path = std::env::temp_dir().join(format!["blrs-test-{}.blend", uuid::Uuid::new_v4()]);
+
+        let block = |code: &[u8; 4], body: &[u8]| {
+            let mut b = code.to_vec();
+            b.extend([0; 12]);
+            b.extend((body.len() as u64).to_le_bytes());
+            b.extend([0; 8]);
+            b.extend(body);
+            b
+        };
+        let mut data = b"BLENDER17-01v0500".to_vec();
+        data.extend(block(b"REND", &[0; 7]));
+        let mut global = b"   2".to_vec();
+        global.extend([2, 0, 244, 1, 1, 0]);
+        data.extend(block(b"GLOB", &global));
+
+        std::fs::write(&path, data).unwrap();
+        let header = read_blendfile_header(&path).unwrap();
+        assert_eq![header.full_version(), Version::new(5, 0, 2)];
+        assert_eq![header.min_version, Some(Version::new(5, 0, 1))];
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_subversion() {
+        let path = std::env::temp_dir().join(format!["blrs-test-{}.blend", uuid::Uuid::new_v4()]);
+
+        std::fs::write(&path, blend_with_global(b"402", 3, (400, 5))).unwrap();
+        let header = read_blendfile_header(&path).unwrap();
+        assert_eq![header.version, (4, 2)];
+        assert_eq![header.subversion, Some(3)];
+        assert_eq![header.full_version(), Version::new(4, 2, 3)];
+        assert_eq![header.min_version, Some(Version::new(4, 0, 5))];
+
+        std::fs::write(&path, b"BLENDER-v402").unwrap();
+        let header = read_blendfile_header(&path).unwrap();
+        assert_eq![header.subversion, None];
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -4,7 +4,7 @@ use std::{
     fmt::Display,
     fs::File,
     hash::Hash,
-    io::{self, Write},
+    io,
     path::{Path, PathBuf},
     str::FromStr,
     sync::LazyLock,
@@ -14,10 +14,16 @@ use chrono::{DateTime, Utc};
 use regex::Regex;
 use semver::{BuildMetadata, Prerelease, Version};
 use serde::{Deserialize, Serialize};
+use url::Url;
 
-use crate::search::{OrdPlacement, VersionSearchQuery, WildPlacement};
+use crate::{
+    atomic::write_atomically,
+    search::{OrdPlacement, VersionSearchQuery, WildPlacement},
+};
 
-use super::{get_info_from_blender, CollectedInfo, VerboseVersion};
+use super::VerboseVersion;
+#[cfg(not(target_arch = "wasm32"))]
+use super::{get_info_from_blender, CollectedInfo};
 
 static MATCHERS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
     [
@@ -52,11 +58,10 @@ fn simple_clean(s: &str) -> &str {
         s = c.get(1).unwrap().as_str();
     }
 
-    if let Some(i) = s.find("-windows") {
-        s = &s[..i];
-    }
-    if let Some(i) = s.find("-linux") {
-        s = &s[..i];
+    for platform in ["-windows", "-linux", "-darwin", "-macos"] {
+        if let Some(i) = s.find(platform) {
+            s = &s[..i];
+        }
     }
 
     s
@@ -81,8 +86,68 @@ const FILE_VERSION: f32 = 1.0;
 /// This function handles various formats of Blender version strings, including older, non-SemVer compatible versions.
 /// It uses regular expressions to extract the major, minor, patch, and prerelease information from the input string.
 /// If the string cannot be parsed into a valid `Version` object, it returns `None`.
-
+///
+/// With the `version-cache` feature, results are memoized by input string.
 pub fn parse_blender_ver(s: &str, search: bool) -> Option<Version> {
+    if let Some(v) = parse_numeric_version(s) {
+        return Some(v);
+    }
+
+    #[cfg(feature = "version-cache")]
+    {
+        if let Some(v) = VERSION_CACHE.lock()[search as usize].get(s) {
+            return v.clone();
+        }
+
+        let v = parse_blender_ver_uncached(s, search);
+        VERSION_CACHE.lock()[search as usize].put(s.to_string(), v.clone());
+        v
+    }
+
+    #[cfg(not(feature = "version-cache"))]
+    parse_blender_ver_uncached(s, search)
+}
+
+/// Parses plain `<major>.<minor>.<patch>` strings like `4.3.0` without touching any regex.
+///
+/// This is the most common shape of version in builder feeds, so it is worth skipping the rest of the parser for.
+fn parse_numeric_version(s: &str) -> Option<Version> {
+    let mut parts = s.trim().split('.');
+    let mut next = || {
+        let part = parts.next()?;
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        part.parse::<u64>().ok()
+    };
+
+    let (major, minor, patch) = (next()?, next()?, next()?);
+    match parts.next() {
+        None => Some(Version::new(major, minor, patch)),
+        Some(_) => None,
+    }
+}
+
+/// The maximum amount of strings held by each of [`VERSION_CACHE`]'s caches, after which the least
+/// recently used ones are evicted.
+#[cfg(feature = "version-cache")]
+const VERSION_CACHE_CAPACITY: std::num::NonZeroUsize = std::num::NonZeroUsize::new(4096).unwrap();
+
+/// Memoized results of [`parse_blender_ver`] keyed by the input string, with one cache for each
+/// value of the `search` flag so lookups don't need to allocate a key.
+#[cfg(feature = "version-cache")]
+type VersionCache = [lru::LruCache<String, Option<Version>>; 2];
+
+/// See [`VersionCache`].
+#[cfg(feature = "version-cache")]
+static VERSION_CACHE: LazyLock<parking_lot::Mutex<VersionCache>> = LazyLock::new(|| {
+    parking_lot::Mutex::new([
+        lru::LruCache::new(VERSION_CACHE_CAPACITY),
+        lru::LruCache::new(VERSION_CACHE_CAPACITY),
+    ])
+});
+
+fn parse_blender_ver_uncached(s: &str, search: bool) -> Option<Version> {
     let mut s = s.trim();
     if let Ok(v) = Version::parse(s) {
         return Some(v);
@@ -137,6 +202,85 @@ impl BasicBuildInfo {
     pub fn version(&self) -> &Version {
         self.ver.v()
     }
+
+    /// The canonical version of the build, with `<branch>.<hash>` build metadata, as
+    /// [`crate::fetching::build_schemas::BlenderBuildSchema::full_version`] makes it.
+    ///
+    /// The branch is normalized with [`crate::info::normalize_branch`], so builds installed before
+    /// branches were normalized get the same version as their remote counterparts.
+    ///
+    /// ```
+    /// use blrs::{info::VerboseVersion, BasicBuildInfo};
+    ///
+    /// let basic = BasicBuildInfo {
+    ///     ver: VerboseVersion::new(4, 3, 0, Some("alpha"), Some("main"), Some("ddc9f92777cd")),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq![basic.full_version().to_string(), "4.3.0-alpha+daily.ddc9f92777cd"];
+    /// ```
+    pub fn full_version(&self) -> Version {
+        let build = format!["{}.{}", self.ver.canonical_branch(), self.ver.build_hash()];
+        Version {
+            build: BuildMetadata::new(&build).unwrap_or_else(|_| self.version().build.clone()),
+            ..self.version().clone()
+        }
+    }
+
+    /// A deterministic key combining the version, branch, and build hash, made from [`BasicBuildInfo::full_version`].
+    ///
+    /// Two builds share an identity only if they are the same build, so this should be
+    /// preferred over display strings when deduplicating or keying maps. Branches are
+    /// compared by their canonical names, so `main` and `daily` builds of the same commit share one.
+    pub fn identity(&self) -> String {
+        self.full_version().to_string()
+    }
+
+    /// The verbose version followed by the commit date, such as `4.3.0 (daily, abc123) 2024-11-20 14:02`.
+    ///
+    /// See [`VerboseVersion::display_verbose`] and [`VerboseVersion::display_short`] for shorter forms.
+    pub fn display_full(&self) -> String {
+        format![
+            "{} {}",
+            self.ver.display_verbose(),
+            self.commit_dt.format("%Y-%m-%d %H:%M")
+        ]
+    }
+    /// Whether this is a stable or LTS release, as opposed to a daily, candidate or experimental build.
+    ///
+    /// Builds are stable when their prerelease is empty, `stable` or `lts`.
+    pub fn is_stable(&self) -> bool {
+        let pre = self.version().pre.as_str();
+        pre.is_empty() || pre.eq_ignore_ascii_case("stable") || pre.eq_ignore_ascii_case("lts")
+    }
+
+    /// The blender.org release notes page for this build's `major.minor`, if it is a stable release.
+    ///
+    /// ```
+    /// use blrs::{info::VerboseVersion, BasicBuildInfo};
+    ///
+    /// let build = |pre| BasicBuildInfo {
+    ///     ver: VerboseVersion::new(4, 2, 3, pre, Some("v42"), None),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq![
+    ///     build(Some("stable")).release_notes_url().unwrap().as_str(),
+    ///     "https://www.blender.org/download/releases/4-2/"
+    /// ];
+    /// assert_eq![build(Some("alpha")).release_notes_url(), None];
+    /// ```
+    pub fn release_notes_url(&self) -> Option<Url> {
+        let v = self.version();
+        self.is_stable()
+            .then(|| {
+                Url::parse(&format![
+                    "https://www.blender.org/download/releases/{}-{}/",
+                    v.major, v.minor
+                ])
+                .ok()
+            })
+            .flatten()
+    }
 }
 impl AsRef<Self> for BasicBuildInfo {
     fn as_ref(&self) -> &Self {
@@ -197,6 +341,10 @@ pub struct LocalBuildInfo {
     /// Whether or not this build is a favorite.
     pub is_favorited: bool,
 
+    /// Whether this build is locked against being deleted by [`crate::repos::prune`], unless it is forced.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_locked: bool,
+
     /// An optional custom name for the build.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_name: Option<String>,
@@ -208,6 +356,41 @@ pub struct LocalBuildInfo {
     /// An optional set of custom environment variables to use when running this build.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_env: Option<HashMap<String, String>>,
+
+    /// The ID of the repo the build was installed from, if it was installed from one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_repo: Option<String>,
+
+    /// The URL the build was downloaded from, if it was downloaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_link: Option<String>,
+}
+
+impl LocalBuildInfo {
+    /// Creates the info of a build that has no personal preferences set yet: it is not favorited or
+    /// locked, and has no custom name, executable, environment, or source.
+    ///
+    /// ```
+    /// use blrs::{info::build_info::LocalBuildInfo, BasicBuildInfo};
+    ///
+    /// let info = LocalBuildInfo {
+    ///     custom_name: Some("My Build".to_string()),
+    ///     ..LocalBuildInfo::new(BasicBuildInfo::default())
+    /// };
+    /// assert![!info.is_favorited];
+    /// ```
+    pub fn new(basic: BasicBuildInfo) -> Self {
+        Self {
+            basic,
+            is_favorited: false,
+            is_locked: false,
+            custom_name: None,
+            custom_exe: None,
+            custom_env: None,
+            source_repo: None,
+            source_link: None,
+        }
+    }
 }
 
 /// This is what a normal `.build_info` file looks like.
@@ -267,6 +450,8 @@ impl LocalBuild {
 
     /// Attempts to generate a `LocalBuild` instance from an executable's path by extracting information
     /// about the build using Blender's internal metadata.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
     pub fn generate_from_exe(executable: &Path) -> io::Result<LocalBuild> {
         let build_path = executable.parent().unwrap();
 
@@ -302,11 +487,8 @@ impl LocalBuild {
                 }
 
                 let local_info = LocalBuildInfo {
-                    basic: basic_info,
-                    is_favorited: false,
                     custom_name,
-                    custom_exe: None,
-                    custom_env: None,
+                    ..LocalBuildInfo::new(basic_info)
                 };
 
                 let local_build = LocalBuild {
@@ -323,19 +505,86 @@ impl LocalBuild {
         })
     }
 
+    /// Locates the `<major>.<minor>` resource folder of the build (e.g. `folder/4.3/`),
+    /// where Blender keeps its bundled scripts, addons, and datafiles.
+    ///
+    /// macOS app bundles keep it in `Blender.app/Contents/Resources/`, which is also checked.
+    /// Returns `None` if the folder can't be found.
+    pub fn resource_dir(&self) -> Option<PathBuf> {
+        let v = self.info.basic.version();
+        let name = format!["{}.{}", v.major, v.minor];
+
+        [
+            self.folder.clone(),
+            self.folder.join("Blender/Blender.app/Contents/Resources"),
+            self.folder.join("Blender.app/Contents/Resources"),
+        ]
+        .into_iter()
+        .map(|p| p.join(&name))
+        .find(|p| p.is_dir())
+    }
+
+    /// Applies `f` to the build's info and saves it to its `.build_info` file.
+    ///
+    /// The file is written atomically. If writing fails, the changes made by `f` are rolled back.
+    pub fn update_info<F>(&mut self, f: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut LocalBuildInfo),
+    {
+        let previous = self.info.clone();
+        f(&mut self.info);
+
+        self.write().inspect_err(|_| self.info = previous)
+    }
+
+    /// Marks the build as a favorite or not, and saves it.
+    pub fn set_favorited(&mut self, favorited: bool) -> io::Result<()> {
+        self.update_info(|info| info.is_favorited = favorited)
+    }
+
+    /// The repo ID and URL the build was installed from, so it can be downloaded again after being deleted.
+    ///
+    /// Builds installed before this was recorded, or that weren't installed from a repo, return `None`.
+    pub fn reinstall_source(&self) -> Option<(String, String)> {
+        Some((
+            self.info.source_repo.clone()?,
+            self.info.source_link.clone()?,
+        ))
+    }
+
+    /// The build's canonical version, the same as a [`crate::RemoteBuild`] of this build has.
+    /// See [`BasicBuildInfo::full_version`].
+    pub fn full_version(&self) -> Version {
+        self.info.basic.full_version()
+    }
+
+    /// Locks or unlocks the build against pruning, and saves it.
+    pub fn set_locked(&mut self, locked: bool) -> io::Result<()> {
+        self.update_info(|info| info.is_locked = locked)
+    }
+
+    /// Sets the build's custom name, and saves it.
+    pub fn set_custom_name(&mut self, name: Option<String>) -> io::Result<()> {
+        self.update_info(|info| info.custom_name = name)
+    }
+
+    /// Sets the build's custom environment variables, and saves it.
+    pub fn set_custom_env(&mut self, env: Option<HashMap<String, String>>) -> io::Result<()> {
+        self.update_info(|info| info.custom_env = env)
+    }
+
     /// Writes the current `LocalBuild` instance to a `.build_info` file.
     pub fn write(&self) -> Result<(), io::Error> {
         self.write_to(self.folder.join(".build_info"))
     }
 
     /// Writes the current `LocalBuild` instance to a given file path.
+    ///
+    /// The file is replaced atomically, so concurrent readers never see a partially written file.
     pub fn write_to(&self, filepath: PathBuf) -> Result<(), io::Error> {
         let data = serde_json::to_string(&BuildInfoSpec::from(self.info.clone())).unwrap();
 
-        let mut file = File::create(filepath)?;
-        file.write_all(data.as_bytes())?;
-
-        Ok(())
+        write_atomically(&filepath, data.as_bytes())
     }
 }
 
@@ -345,11 +594,11 @@ mod tests {
 
     use semver::{BuildMetadata, Prerelease, Version};
 
-    use crate::info::parse_blender_ver;
+    use crate::info::{parse_blender_ver, BasicBuildInfo, LocalBuild};
 
-    use super::VerboseVersion;
+    use super::{parse_numeric_version, LocalBuildInfo, VerboseVersion};
 
-    const TEST_STRINGS: LazyLock<[(&str, Version); 12]> = LazyLock::new(|| {
+    static TEST_STRINGS: LazyLock<[(&str, Version); 12]> = LazyLock::new(|| {
         [
             ("Blender1.0", Version::parse("1.0.0").unwrap()),
             (
@@ -405,6 +654,26 @@ mod tests {
         ]
     });
 
+    #[test]
+    fn test_multi_segment_metadata() {
+        let cases = [
+            (
+                "blender-4.2.0-alpha+main-PR109522.f723782e3a8c-darwin.arm64-release.dmg",
+                ("main-PR109522", "f723782e3a8c"),
+            ),
+            (
+                "4.2.0-alpha+main.PR109522.f723782e3a8c",
+                ("main.PR109522", "f723782e3a8c"),
+            ),
+            ("4.2.0+main", ("null", "ffffffff")),
+        ];
+
+        for (s, (branch, hash)) in cases {
+            let v = VerboseVersion::from(parse_blender_ver(s, true).unwrap());
+            assert_eq![(v.branch(), v.build_hash()), (branch, hash), "{}", s];
+        }
+    }
+
     #[test]
     fn test_parser() {
         println!["{:#?}", TEST_STRINGS];
@@ -415,6 +684,19 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_numeric_fast_path() {
+        assert_eq!(parse_numeric_version("4.3.0"), Some(Version::new(4, 3, 0)));
+        assert_eq!(
+            parse_numeric_version(" 2.93.18 "),
+            Some(Version::new(2, 93, 18))
+        );
+        assert_eq!(parse_numeric_version("4.3"), None);
+        assert_eq!(parse_numeric_version("4.3.0.1"), None);
+        assert_eq!(parse_numeric_version("4.3.0-alpha"), None);
+        assert_eq!(parse_numeric_version("4..0"), None);
+    }
+
     #[test]
     fn test_blend_build_methods() {
         let ver = VerboseVersion::default();
@@ -423,4 +705,46 @@ mod tests {
         assert_eq!(ver.branch(), "null");
         assert_eq!(ver.build_hash(), "ffffffff");
     }
+
+    #[test]
+    fn test_identity() {
+        let a = BasicBuildInfo {
+            ver: VerboseVersion::new(4, 3, 0, Some("alpha"), Some("main"), Some("ddc9f92777cd")),
+            ..Default::default()
+        };
+        let b = BasicBuildInfo {
+            ver: a.ver.clone().with_branch(Some("daily")).unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(a.identity(), "4.3.0-alpha+daily.ddc9f92777cd");
+        assert_eq!(a.identity(), b.identity());
+        assert_eq!(BasicBuildInfo::default().identity(), "0.0.0+null.ffffffff");
+    }
+
+    #[test]
+    fn test_reinstall_source() {
+        let legacy: LocalBuildInfo = serde_json::from_str(
+            r#"{"basic": {"ver": {"v": "4.3.0", "hash_split": 4}, "commit_dt": "2024-11-01T00:00:00Z"}, "is_favorited": false}"#,
+        )
+        .unwrap();
+        let mut build = LocalBuild {
+            folder: "4.3.0".into(),
+            info: legacy,
+        };
+        assert_eq![build.reinstall_source(), None];
+
+        build.info.source_repo = Some("builder.blender.org.daily".to_string());
+        build.info.source_link = Some("https://builder.blender.org/blender.tar.xz".to_string());
+        let info: LocalBuildInfo =
+            serde_json::from_str(&serde_json::to_string(&build.info).unwrap()).unwrap();
+        assert_eq![info, build.info];
+        assert_eq![
+            build.reinstall_source(),
+            Some((
+                "builder.blender.org.daily".to_string(),
+                "https://builder.blender.org/blender.tar.xz".to_string()
+            ))
+        ];
+    }
 }
@@ -1,7 +1,24 @@
-use std::{collections::HashMap, env::consts::OS, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env::consts::OS,
+    fmt::Display,
+    io,
+    path::PathBuf,
+    process::{Child, Command, ExitStatus, Output, Stdio},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::BLRSConfig;
 
 use super::LocalBuild;
 
+mod interpolation;
+mod shortcut;
+pub use interpolation::{interpolate_env, EnvInterpolation};
+
 /// An enum specifying stuff fed to blender when built.
 #[derive(Clone, Debug, Default)]
 pub enum BlendLaunchTarget {
@@ -39,19 +56,73 @@ impl BlendLaunchTarget {
 }
 
 /// An enum specifying the target OS and its specific launch configuration.
-#[derive(Clone, Debug)]
+///
+/// It is serialized with an `os` tag, like `{ "os": "windows", "no_console": true }`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "os", rename_all = "lowercase")]
 pub enum OSLaunchTarget {
     /// Linux environment.
     Linux,
     /// Windows environment with optional console flag.
     Windows {
         /// Whether to launch Blender without a console window. This is relevant for GUI-based launches.
+        #[serde(default)]
         no_console: bool,
     },
     /// macOS environment.
+    #[serde(rename = "macos")]
     MacOS,
 }
 
+/// The error returned when a string does not name an [`OSLaunchTarget`].
+#[derive(Debug, Clone, Error)]
+#[error("Unknown launch target {0:?}, expected one of: linux, windows, windows-noconsole, macos")]
+pub struct ParseOSLaunchTargetError(pub String);
+
+impl FromStr for OSLaunchTarget {
+    type Err = ParseOSLaunchTargetError;
+
+    /// Parses the names used by [`OSLaunchTarget`]'s `Display`, ignoring case.
+    ///
+    /// ```
+    /// use blrs::info::launching::OSLaunchTarget;
+    ///
+    /// let target: OSLaunchTarget = "windows-noconsole".parse().unwrap();
+    /// assert_eq![target, OSLaunchTarget::Windows { no_console: true }];
+    /// assert_eq![target.to_string(), "windows-noconsole"];
+    /// assert_eq![serde_json::to_string(&target).unwrap(), r#"{"os":"windows","no_console":true}"#];
+    /// assert![OSLaunchTarget::try_from("haiku").is_err()];
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "linux" => Ok(Self::Linux),
+            "windows" => Ok(Self::Windows { no_console: false }),
+            "windows-noconsole" => Ok(Self::Windows { no_console: true }),
+            "macos" => Ok(Self::MacOS),
+            _ => Err(ParseOSLaunchTargetError(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<&str> for OSLaunchTarget {
+    type Error = ParseOSLaunchTargetError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for OSLaunchTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OSLaunchTarget::Linux => "linux",
+            OSLaunchTarget::Windows { no_console: false } => "windows",
+            OSLaunchTarget::Windows { no_console: true } => "windows-noconsole",
+            OSLaunchTarget::MacOS => "macos",
+        })
+    }
+}
+
 impl Default for OSLaunchTarget {
     #[inline]
     fn default() -> Self {
@@ -107,10 +178,64 @@ impl GeneratedParams {
             ..Default::default()
         }
     }
+
+    /// Spawns Blender interactively. Its stdio is inherited from the current process.
+    pub fn spawn(self) -> io::Result<Child> {
+        Command::from(self).spawn()
+    }
+
+    /// Runs Blender and blocks until it exits, returning its exit status.
+    ///
+    /// Its stdio is inherited from the current process, like [`GeneratedParams::spawn`].
+    /// On macOS, the status is the one of `open`, see [`LaunchArguments::assemble_for`].
+    pub fn run(self) -> io::Result<ExitStatus> {
+        Command::from(self).status()
+    }
+
+    /// Like [`GeneratedParams::run`], but waits for Blender without blocking the async runtime.
+    ///
+    /// The process is waited on from tokio's blocking thread pool.
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub async fn run_async(self) -> io::Result<ExitStatus> {
+        tokio::task::spawn_blocking(move || self.run())
+            .await
+            .map_err(io::Error::other)?
+    }
+
+    /// Runs Blender to completion while capturing its stdout and stderr.
+    ///
+    /// Unlike [`GeneratedParams::spawn`], this blocks until Blender exits, which makes it useful
+    /// for background (`-b`) renders or for showing the user why a launch failed.
+    pub fn run_captured(self) -> io::Result<Output> {
+        Command::from(self)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+    }
 }
-#[derive(Clone, Debug)]
+
+impl From<GeneratedParams> for Command {
+    fn from(params: GeneratedParams) -> Self {
+        let mut cmd = Command::new(params.exe);
+        if let Some(args) = params.args {
+            cmd.args(args);
+        }
+        if let Some(env) = params.env {
+            cmd.envs(env);
+        }
+
+        cmd
+    }
+}
+#[derive(Clone, Debug, Error)]
 /// Errors related to generating parameters.
-pub enum ArgGenerationError {}
+pub enum ArgGenerationError {
+    /// An environment variable referenced a variable that is not set, with [`EnvInterpolation::Strict`].
+    #[error("The environment variable {0:?} is not set")]
+    UnresolvedVariable(String),
+}
 
 /// Struct holding the arguments required to launch Blender with specific configurations.
 #[derive(Clone, Debug)]
@@ -123,6 +248,9 @@ pub struct LaunchArguments {
 
     /// Optional environment variables to be passed to Blender.
     pub env: Option<HashMap<String, String>>,
+
+    /// How variable references in the environment variables are expanded.
+    pub env_interpolation: EnvInterpolation,
 }
 
 impl LaunchArguments {
@@ -132,27 +260,60 @@ impl LaunchArguments {
             file_target: file,
             os_target: OSLaunchTarget::try_default().unwrap(),
             env: None,
+            env_interpolation: EnvInterpolation::default(),
         }
     }
 
-    /// Resolves the launching arguments and creates the params required to launch blender
+    /// Like [`LaunchArguments::file`], but launches with the config's [`BLRSConfig::os_launch_target`].
+    ///
+    /// Returns `None` if the config has no launch target and launching is not supported on the current OS.
+    ///
+    /// ```
+    /// use blrs::{info::launching::{BlendLaunchTarget, LaunchArguments, OSLaunchTarget}, BLRSConfig};
+    ///
+    /// let mut config = BLRSConfig::default();
+    /// config.default_os_launch = Some(OSLaunchTarget::Windows { no_console: true });
+    ///
+    /// let args = LaunchArguments::file_with_config(BlendLaunchTarget::None, &config).unwrap();
+    /// assert_eq![args.os_target, OSLaunchTarget::Windows { no_console: true }];
+    /// ```
+    pub fn file_with_config(file: BlendLaunchTarget, config: &BLRSConfig) -> Option<Self> {
+        Some(LaunchArguments {
+            file_target: file,
+            os_target: config.os_launch_target()?,
+            env: None,
+            env_interpolation: EnvInterpolation::default(),
+        })
+    }
+
+    /// Resolves the launching arguments and creates the params required to launch blender.
+    ///
+    /// The params wait for Blender to exit when they are run, see [`LaunchArguments::assemble_for`].
     pub fn assemble(self, lb: &LocalBuild) -> Result<GeneratedParams, ArgGenerationError> {
-        let blender = lb.folder.join(
-            lb.info
-                .custom_exe
-                .clone()
-                .unwrap_or(self.os_target.exe_name().to_string()),
-        );
+        self.assemble_for(lb, true)
+    }
+
+    /// Like [`LaunchArguments::assemble`], but chooses whether running the params waits for Blender to exit.
+    ///
+    /// This only changes anything on macOS, where Blender is launched through `open`. With `wait`,
+    /// `open -W` is used, so `open` only exits once Blender does and can be used with [`GeneratedParams::run`].
+    /// Without it, `open` returns as soon as Blender has been launched, which suits [`GeneratedParams::spawn`]
+    /// for detached launches.
+    /// Either way, the exit status is `open`'s own, which only tells whether Blender could be launched,
+    /// and not how it exited.
+    pub fn assemble_for(
+        self,
+        lb: &LocalBuild,
+        wait: bool,
+    ) -> Result<GeneratedParams, ArgGenerationError> {
+        let blender = lb.find_executable(&self.os_target);
 
         let (executable, args) = match self.os_target {
             OSLaunchTarget::Linux => (blender, None),
             OSLaunchTarget::Windows { no_console: _ } => (blender, None),
             OSLaunchTarget::MacOS => {
-                let mut args = vec![
-                    "-W".to_string(),
-                    "-n".to_string(),
-                    blender.to_str().unwrap().to_string(),
-                ];
+                let mut args: Vec<String> = wait.then(|| "-W".to_string()).into_iter().collect();
+                args.extend(["-n".to_string(), blender.to_str().unwrap().to_string()]);
 
                 match self.file_target {
                     BlendLaunchTarget::None => {}
@@ -184,11 +345,80 @@ impl LaunchArguments {
                     new_env.extend(genv);
                     Some(new_env)
                 }
-            },
+            }
+            .map(|env| interpolate_env(&env, self.env_interpolation))
+            .transpose()?,
         })
     }
 }
 
+/// Reasons a build may not be ready to launch.
+#[derive(Debug, Error)]
+pub enum LaunchReadiness {
+    /// The current OS has no known launch target.
+    #[error("Launching is not supported on this OS")]
+    UnsupportedOS,
+    /// The executable does not exist.
+    #[error("The executable {0:?} does not exist")]
+    MissingExecutable(PathBuf),
+    /// The executable exists, but is not something that can be run.
+    #[error("{0:?} is not a runnable executable")]
+    NotExecutable(PathBuf),
+    /// The executable could not be inspected.
+    #[error("Could not inspect the executable: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl LocalBuild {
+    /// Resolves the path to the executable for the given OS target, respecting the build's `custom_exe`.
+    pub fn find_executable(&self, os_target: &OSLaunchTarget) -> PathBuf {
+        self.folder.join(
+            self.info
+                .custom_exe
+                .clone()
+                .unwrap_or(os_target.exe_name().to_string()),
+        )
+    }
+
+    /// Checks whether this build could be launched on the current OS without actually spawning it.
+    ///
+    /// This verifies that the executable exists and, on unix, that it has an executable permission bit.
+    /// On macOS the executable is an app bundle, so it only needs to be a directory.
+    pub fn launchable(&self) -> Result<(), LaunchReadiness> {
+        let os_target = OSLaunchTarget::try_default().ok_or(LaunchReadiness::UnsupportedOS)?;
+        let exe = self.find_executable(&os_target);
+
+        let metadata = match exe.metadata() {
+            Ok(m) => m,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(LaunchReadiness::MissingExecutable(exe))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let runnable = match os_target {
+            OSLaunchTarget::MacOS if self.info.custom_exe.is_none() => metadata.is_dir(),
+            _ => metadata.is_file() && is_executable(&metadata),
+        };
+
+        match runnable {
+            true => Ok(()),
+            false => Err(LaunchReadiness::NotExecutable(exe)),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use std::{path::PathBuf, sync::LazyLock, time::SystemTime};
@@ -197,21 +427,17 @@ mod tests {
 
     use crate::info::{
         build_info::LocalBuildInfo,
-        launching::{BlendLaunchTarget, GeneratedParams, LaunchArguments, OSLaunchTarget},
+        launching::{
+            BlendLaunchTarget, GeneratedParams, LaunchArguments, LaunchReadiness, OSLaunchTarget,
+        },
         BasicBuildInfo, LocalBuild, VerboseVersion,
     };
-    const TEST_BUILD: LazyLock<LocalBuild> = LazyLock::new(|| LocalBuild {
+    static TEST_BUILD: LazyLock<LocalBuild> = LazyLock::new(|| LocalBuild {
         folder: PathBuf::from("blender/"),
-        info: LocalBuildInfo {
-            basic: BasicBuildInfo {
-                ver: VerboseVersion::new(4, 3, 0, None, None, None),
-                commit_dt: DateTime::from(SystemTime::now()),
-            },
-            is_favorited: false,
-            custom_name: None,
-            custom_exe: None,
-            custom_env: None,
-        },
+        info: LocalBuildInfo::new(BasicBuildInfo {
+            ver: VerboseVersion::new(4, 3, 0, None, None, None),
+            commit_dt: DateTime::from(SystemTime::now()),
+        }),
     });
 
     #[test]
@@ -221,6 +447,7 @@ mod tests {
                 file_target: BlendLaunchTarget::None,
                 os_target: OSLaunchTarget::Linux,
                 env: None,
+                env_interpolation: Default::default(),
             }
             .assemble(&TEST_BUILD)
             .unwrap(),
@@ -231,6 +458,7 @@ mod tests {
                 file_target: BlendLaunchTarget::OpenLast,
                 os_target: OSLaunchTarget::Linux,
                 env: None,
+                env_interpolation: Default::default(),
             }
             .assemble(&TEST_BUILD)
             .unwrap(),
@@ -246,6 +474,7 @@ mod tests {
                 file_target: BlendLaunchTarget::File(PathBuf::from("blendfile.blend")),
                 os_target: OSLaunchTarget::Linux,
                 env: None,
+                env_interpolation: Default::default(),
             }
             .assemble(&TEST_BUILD)
             .unwrap(),
@@ -263,7 +492,8 @@ mod tests {
                     "file.blend".to_string()
                 ]),
                 os_target: OSLaunchTarget::Linux,
-                env: None
+                env: None,
+                env_interpolation: Default::default(),
             }
             .assemble(&TEST_BUILD)
             .unwrap(),
@@ -278,4 +508,53 @@ mod tests {
             },
         ];
     }
+
+    #[test]
+    fn test_macos_wait_modes() {
+        let args = |wait: bool| {
+            LaunchArguments {
+                file_target: BlendLaunchTarget::OpenLast,
+                os_target: OSLaunchTarget::MacOS,
+                env: None,
+                env_interpolation: Default::default(),
+            }
+            .assemble_for(&TEST_BUILD, wait)
+            .unwrap()
+            .args
+            .unwrap()
+        };
+
+        assert_eq![
+            args(true),
+            [
+                "-W",
+                "-n",
+                "blender/Blender/Blender.app",
+                "--args",
+                "--open-last"
+            ]
+        ];
+        assert_eq![
+            args(false),
+            ["-n", "blender/Blender/Blender.app", "--args", "--open-last"]
+        ];
+    }
+
+    #[test]
+    fn test_launchable_missing_exe() {
+        assert![matches![
+            TEST_BUILD.launchable(),
+            Err(LaunchReadiness::MissingExecutable(_))
+        ]];
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        assert![GeneratedParams::from_exe("true").run().unwrap().success()];
+        assert![!GeneratedParams::from_exe("false").run().unwrap().success()];
+        assert![GeneratedParams::from_exe("/nonexistent/blender")
+            .run()
+            .is_err()];
+    }
 }
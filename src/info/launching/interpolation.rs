@@ -0,0 +1,129 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::ArgGenerationError;
+
+/// How `${VAR}` and `$VAR` references in a build's environment variables are handled when launching.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnvInterpolation {
+    /// Values are passed to Blender exactly as they are written.
+    #[default]
+    Verbatim,
+    /// References are expanded, and ones that cannot be resolved are left as they are written.
+    Lenient,
+    /// References are expanded, and ones that cannot be resolved are an error.
+    Strict,
+}
+
+/// Expands the `${VAR}` and `$VAR` references in every value of `env`. `$$` is an escaped `$`.
+///
+/// References are resolved against the other keys of `env` first, and then against the
+/// current process's environment. References that loop back onto themselves are unresolved.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use blrs::info::launching::{interpolate_env, EnvInterpolation};
+///
+/// let env = HashMap::from([
+///     ("CACHE".to_string(), "${ROOT}/cache".to_string()),
+///     ("ROOT".to_string(), "/tmp/$USER_THAT_DOES_NOT_EXIST".to_string()),
+///     ("PRICE".to_string(), "$$5".to_string()),
+/// ]);
+///
+/// let expanded = interpolate_env(&env, EnvInterpolation::Lenient).unwrap();
+/// assert_eq![expanded["CACHE"], "/tmp/$USER_THAT_DOES_NOT_EXIST/cache"];
+/// assert_eq![expanded["PRICE"], "$5"];
+/// assert![interpolate_env(&env, EnvInterpolation::Strict).is_err()];
+/// ```
+pub fn interpolate_env(
+    env: &HashMap<String, String>,
+    mode: EnvInterpolation,
+) -> Result<HashMap<String, String>, ArgGenerationError> {
+    if mode == EnvInterpolation::Verbatim {
+        return Ok(env.clone());
+    }
+
+    let mut resolver = Resolver {
+        env,
+        strict: mode == EnvInterpolation::Strict,
+        visiting: HashSet::new(),
+    };
+    env.iter()
+        .map(|(k, v)| {
+            resolver.visiting.insert(k.as_str());
+            let expanded = resolver.expand(v);
+            resolver.visiting.remove(k.as_str());
+            Ok((k.clone(), expanded?))
+        })
+        .collect()
+}
+
+struct Resolver<'a> {
+    env: &'a HashMap<String, String>,
+    strict: bool,
+    /// The keys currently being expanded, to catch references that loop.
+    visiting: HashSet<&'a str>,
+}
+
+impl<'a> Resolver<'a> {
+    fn lookup(&mut self, name: &str) -> Result<Option<String>, ArgGenerationError> {
+        let env = self.env;
+        match env.get_key_value(name) {
+            Some((key, value)) if !self.visiting.contains(key.as_str()) => {
+                self.visiting.insert(key);
+                let expanded = self.expand(value);
+                self.visiting.remove(key.as_str());
+                expanded.map(Some)
+            }
+            Some(_) => Ok(None),
+            None => Ok(std::env::var(name).ok()),
+        }
+    }
+
+    fn expand(&mut self, value: &str) -> Result<String, ArgGenerationError> {
+        let mut out = String::with_capacity(value.len());
+        let mut rest = value;
+
+        while let Some(i) = rest.find('$') {
+            out.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+
+            let (name, written) = if let Some(r) = rest.strip_prefix('$') {
+                out.push('$');
+                rest = r;
+                continue;
+            } else if let Some(r) = rest.strip_prefix('{') {
+                match r.find('}') {
+                    Some(end) => (&r[..end], &rest[..end + 2]),
+                    None => ("", ""),
+                }
+            } else {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[..end])
+            };
+
+            if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+                out.push('$');
+                continue;
+            }
+            rest = &rest[written.len()..];
+
+            match self.lookup(name)? {
+                Some(v) => out.push_str(&v),
+                None if self.strict => {
+                    return Err(ArgGenerationError::UnresolvedVariable(name.to_string()))
+                }
+                None => {
+                    out.push('$');
+                    out.push_str(written);
+                }
+            }
+        }
+
+        out.push_str(rest);
+        Ok(out)
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[cfg(windows)]
+use mslnk::{ShellLink, ShowCommand};
+
+use super::{BlendLaunchTarget, GeneratedParams, LaunchArguments, OSLaunchTarget};
+use crate::{atomic::write_atomically, LocalBuild};
+
+impl LocalBuild {
+    /// Writes a desktop shortcut into the folder `dest` that launches this build with `target` on
+    /// `os_target`, and returns the path of the shortcut.
+    ///
+    /// Use [`crate::BLRSConfig::os_launch_target`] for `os_target` to honor the user's configured launch target.
+    /// The shortcut embeds the command assembled by [`LaunchArguments::assemble_for`], including the
+    /// build's custom environment variables. What gets written depends on `os_target`:
+    /// - Linux: a `.desktop` entry.
+    /// - Windows: a `.lnk` shortcut. Shortcuts can't set environment variables, so when there are any, the
+    ///   shortcut starts Blender through a minimized `cmd.exe`. These can only be written on Windows.
+    /// - macOS: a Finder alias of the build's app, made with `osascript`, so it can only be written on macOS.
+    ///   Aliases can't carry arguments or environment variables, so only [`BlendLaunchTarget::None`] without
+    ///   environment variables is supported, and [`io::ErrorKind::Unsupported`] is returned otherwise.
+    ///
+    /// The file is named after the build's custom name, or its version if it has none.
+    pub fn create_launcher_shortcut(
+        &self,
+        target: &BlendLaunchTarget,
+        os_target: &OSLaunchTarget,
+        dest: &Path,
+    ) -> io::Result<PathBuf> {
+        let params = LaunchArguments {
+            os_target: os_target.clone(),
+            ..LaunchArguments::file(target.clone())
+        }
+        .assemble_for(self, false)
+        .map_err(io::Error::other)?;
+
+        let name = self
+            .info
+            .custom_name
+            .clone()
+            .unwrap_or_else(|| format!["Blender {}", self.info.basic.ver.display_short()]);
+        let file_stem: String = name
+            .chars()
+            .map(|c| match c.is_alphanumeric() || "-_.".contains(c) {
+                true => c,
+                false => '-',
+            })
+            .collect();
+
+        fs::create_dir_all(dest)?;
+        match os_target {
+            OSLaunchTarget::Linux => {
+                let path = dest.join(format!["{}.desktop", file_stem]);
+                write_atomically(&path, self.desktop_entry(&name, &params).as_bytes())?;
+
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+                }
+
+                Ok(path)
+            }
+            #[cfg(windows)]
+            OSLaunchTarget::Windows { .. } => {
+                let path = dest.join(format!["{}.lnk", file_stem]);
+                self.write_lnk(&name, &params, &path)?;
+                Ok(path)
+            }
+            #[cfg(not(windows))]
+            OSLaunchTarget::Windows { .. } => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                ".lnk shortcuts can only be written on Windows",
+            )),
+            OSLaunchTarget::MacOS => {
+                if !matches![target, BlendLaunchTarget::None]
+                    || params.env.as_ref().is_some_and(|e| !e.is_empty())
+                {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "Finder aliases can't carry arguments or environment variables",
+                    ));
+                }
+
+                let app = self.find_executable(os_target);
+                make_alias(&app, dest, &file_stem)?;
+                Ok(dest.join(file_stem))
+            }
+        }
+    }
+
+    /// A Windows shell link that starts the params.
+    #[cfg(windows)]
+    fn write_lnk(&self, name: &str, params: &GeneratedParams, path: &Path) -> io::Result<()> {
+        let args: Vec<String> = params
+            .args
+            .iter()
+            .flatten()
+            .map(|a| windows_quote(a))
+            .collect();
+        let env: Vec<_> = params.env.iter().flatten().collect();
+
+        let mut link = match env.is_empty() {
+            true => {
+                let mut link = ShellLink::new(&params.exe).map_err(io::Error::other)?;
+                link.set_arguments((!args.is_empty()).then(|| args.join(" ")));
+                link
+            }
+            false => {
+                // Shortcuts can't set environment variables, so they are set by a minimized cmd.exe
+                let cmd = std::env::var_os("COMSPEC")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| PathBuf::from(r"C:\Windows\System32\cmd.exe"));
+                let mut command = String::from("/c");
+                for (k, v) in env {
+                    command += &format![" set \"{}={}\" &&", k, v];
+                }
+                command += &format![
+                    " start \"\" {}",
+                    windows_quote(&params.exe.to_string_lossy())
+                ];
+                for arg in args {
+                    command += &format![" {}", arg];
+                }
+
+                let mut link = ShellLink::new(cmd).map_err(io::Error::other)?;
+                link.set_arguments(Some(command));
+                link.header_mut()
+                    .set_show_command(ShowCommand::ShowMinNoActive);
+                link
+            }
+        };
+
+        link.set_name(Some(name.to_string()));
+        link.set_working_dir(Some(self.folder.to_string_lossy().to_string()));
+        link.set_icon_location(Some(params.exe.to_string_lossy().to_string()));
+        link.create_lnk(path).map_err(io::Error::other)
+    }
+
+    /// A freedesktop.org desktop entry for the params.
+    fn desktop_entry(&self, name: &str, params: &GeneratedParams) -> String {
+        let icon = self.folder.join("blender.svg");
+        let icon = match icon.exists() {
+            true => icon.to_string_lossy().to_string(),
+            false => "blender".to_string(),
+        };
+
+        // Environment variables can't be set by the entry itself, so they are set through `env`
+        let env: Vec<String> = params
+            .env
+            .iter()
+            .flatten()
+            .map(|(k, v)| format!["{}={}", k, v])
+            .collect();
+        let exec: Vec<String> = (!env.is_empty())
+            .then(|| "env".to_string())
+            .into_iter()
+            .chain(env)
+            .chain([params.exe.to_string_lossy().to_string()])
+            .chain(params.args.iter().flatten().cloned())
+            .map(|arg| desktop_quote(&arg))
+            .collect();
+
+        format![
+            "[Desktop Entry]\nType=Application\nName={}\nIcon={}\nExec={}\nPath={}\nTerminal=false\nCategories=Graphics;3DGraphics;\n",
+            name,
+            icon,
+            exec.join(" "),
+            self.folder.to_string_lossy(),
+        ]
+    }
+}
+
+/// Quotes an argument of a desktop entry's `Exec` key, following the desktop entry spec.
+fn desktop_quote(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c))
+    {
+        return arg;
+    }
+
+    let mut quoted = String::from('"');
+    for c in arg.chars() {
+        if "\"`$\\".contains(c) {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Quotes an argument for a Windows command line, as parsed by `CommandLineToArgvW`.
+#[cfg(any(windows, test))]
+fn windows_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.chars().any(|c| c.is_whitespace() || c == '"') {
+        return arg.to_string();
+    }
+
+    // Backslashes are only special right before a quote, where they have to be doubled
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted += &"\\".repeat(backslashes + 1);
+                backslashes = 0;
+            }
+            _ => backslashes = 0,
+        }
+        quoted.push(c);
+    }
+    quoted += &"\\".repeat(backslashes);
+    quoted.push('"');
+    quoted
+}
+
+/// Makes a Finder alias of `target` in `dest` named `name`, through `osascript`.
+fn make_alias(target: &Path, dest: &Path, name: &str) -> io::Result<()> {
+    let output = Command::new("osascript")
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "tell application \"Finder\" to make alias file to (POSIX file (item 1 of argv)) at (POSIX file (item 2 of argv)) with properties {name:(item 3 of argv)}",
+            "-e",
+            "end run",
+        ])
+        .arg(target)
+        .arg(dest)
+        .arg(name)
+        .output()?;
+
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        info::{
+            build_info::LocalBuildInfo,
+            launching::{BlendLaunchTarget, OSLaunchTarget},
+        },
+        LocalBuild,
+    };
+
+    use super::{desktop_quote, windows_quote};
+
+    #[test]
+    fn test_quoting() {
+        assert_eq![
+            desktop_quote("/opt/blender/blender"),
+            "/opt/blender/blender"
+        ];
+        assert_eq![
+            desktop_quote("/home/me/my $file.blend"),
+            r#""/home/me/my \$file.blend""#
+        ];
+        assert_eq![desktop_quote("100%"), "100%%"];
+
+        assert_eq![windows_quote(r"C:\file.blend"), r"C:\file.blend"];
+        assert_eq![windows_quote(r"C:\my file.blend"), r#""C:\my file.blend""#];
+        assert_eq![windows_quote(r#"say "hi"\"#), r#""say \"hi\"\\""#];
+    }
+
+    #[test]
+    fn test_lnk_shortcut() {
+        let dir = std::env::temp_dir().join(format!["blrs-test-{}", uuid::Uuid::new_v4()]);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("blender.exe"), b"").unwrap();
+        let build = LocalBuild {
+            folder: dir.clone(),
+            info: LocalBuildInfo {
+                custom_name: Some("My Build".to_string()),
+                ..LocalBuildInfo::new(Default::default())
+            },
+        };
+
+        let path = build.create_launcher_shortcut(
+            &BlendLaunchTarget::File(dir.join("scene.blend")),
+            &OSLaunchTarget::Windows { no_console: false },
+            &dir.join("shortcuts"),
+        );
+        let data = path.as_ref().map(|p| fs::read(p).unwrap());
+        let alias = build.create_launcher_shortcut(
+            &BlendLaunchTarget::OpenLast,
+            &OSLaunchTarget::MacOS,
+            &dir.join("shortcuts"),
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        #[cfg(windows)]
+        {
+            assert_eq![path.unwrap().file_name().unwrap(), "My-Build.lnk"];
+            assert_eq![data.unwrap()[..4], [0x4c, 0, 0, 0]];
+        }
+        #[cfg(not(windows))]
+        assert_eq![data.unwrap_err().kind(), std::io::ErrorKind::Unsupported];
+        assert_eq![alias.unwrap_err().kind(), std::io::ErrorKind::Unsupported];
+    }
+}
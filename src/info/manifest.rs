@@ -0,0 +1,214 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{atomic::write_atomically, fetching::checksums::generate_sha256, LocalBuild};
+
+/// The name of the manifest file written into a build's folder.
+pub const MANIFEST_FILENAME: &str = ".build_manifest";
+
+/// A file listed in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// The path of the file relative to the build's folder, separated with `/`.
+    pub path: String,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The SHA-256 hash of the file, if hashes were computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// A list of every file in a build's folder, used to check its integrity and size without walking the folder.
+///
+/// The `.build_info` file and the manifest itself are not listed, as they change after installing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The files of the build, sorted by path.
+    pub files: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Lists every file in `folder`. With `hash`, each file's SHA-256 is computed as well, which
+    /// reads the whole build and is much slower.
+    ///
+    /// Symlinks are listed with the size of the link itself, and are never followed or hashed.
+    pub fn generate(folder: &Path, hash: bool) -> io::Result<Self> {
+        let mut files = vec![];
+        walk(folder, folder, hash, &mut files)?;
+        files.sort_by(|a: &ManifestEntry, b| a.path.cmp(&b.path));
+
+        Ok(Self { files })
+    }
+
+    /// The combined size of every file in the manifest, in bytes.
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|f| f.size).sum()
+    }
+
+    /// Compares the manifest against the files currently in `folder`.
+    ///
+    /// Files are compared by size, and by hash if the manifest has one for them.
+    pub fn verify(&self, folder: &Path) -> io::Result<IntegrityReport> {
+        let hash = self.files.iter().any(|f| f.sha256.is_some());
+        let current: HashMap<String, ManifestEntry> = Manifest::generate(folder, hash)?
+            .files
+            .into_iter()
+            .map(|f| (f.path.clone(), f))
+            .collect();
+
+        let mut report = IntegrityReport::default();
+        for expected in &self.files {
+            match current.get(&expected.path) {
+                None => report.missing.push(expected.path.clone()),
+                Some(found)
+                    if found.size != expected.size
+                        || (expected.sha256.is_some() && found.sha256 != expected.sha256) =>
+                {
+                    report.modified.push(expected.path.clone())
+                }
+                Some(_) => {}
+            }
+        }
+
+        let listed: Vec<&str> = self.files.iter().map(|f| f.path.as_str()).collect();
+        report.added = current
+            .into_keys()
+            .filter(|p| !listed.contains(&p.as_str()))
+            .collect();
+        report.added.sort();
+
+        Ok(report)
+    }
+}
+
+/// The differences between a build's [`Manifest`] and its folder, from [`LocalBuild::verify_integrity`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Files in the manifest that no longer exist.
+    pub missing: Vec<String>,
+    /// Files whose size or hash differ from the manifest.
+    pub modified: Vec<String>,
+    /// Files that exist but are not in the manifest.
+    pub added: Vec<String>,
+}
+
+impl IntegrityReport {
+    /// Whether the folder matches the manifest exactly.
+    pub fn is_intact(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty() && self.added.is_empty()
+    }
+}
+
+fn walk(root: &Path, dir: &Path, hash: bool, files: &mut Vec<ManifestEntry>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            walk(root, &path, hash, files)?;
+            continue;
+        }
+
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if relative == ".build_info" || relative == MANIFEST_FILENAME {
+            continue;
+        }
+
+        files.push(ManifestEntry {
+            path: relative,
+            size: entry.metadata()?.len(),
+            sha256: match hash && file_type.is_file() {
+                true => Some(generate_sha256(&path)?),
+                false => None,
+            },
+        });
+    }
+
+    Ok(())
+}
+
+impl LocalBuild {
+    fn manifest_path(&self) -> PathBuf {
+        self.folder.join(MANIFEST_FILENAME)
+    }
+
+    /// Lists the build's files into its folder's [`MANIFEST_FILENAME`], and returns the manifest.
+    ///
+    /// With `hash`, the SHA-256 of every file is stored too, see [`Manifest::generate`].
+    pub fn write_manifest(&self, hash: bool) -> io::Result<Manifest> {
+        let manifest = Manifest::generate(&self.folder, hash)?;
+        let data = serde_json::to_string(&manifest).map_err(io::Error::other)?;
+        write_atomically(&self.manifest_path(), data.as_bytes())?;
+
+        Ok(manifest)
+    }
+
+    /// Reads the build's manifest, written by [`LocalBuild::write_manifest`].
+    ///
+    /// Returns an error with [`io::ErrorKind::NotFound`] if the build has no manifest.
+    pub fn manifest(&self) -> io::Result<Manifest> {
+        let data = fs::read(self.manifest_path())?;
+        serde_json::from_slice(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Compares the build's folder against its manifest, to find missing or user-modified files.
+    ///
+    /// Returns an error with [`io::ErrorKind::NotFound`] if the build has no manifest.
+    pub fn verify_integrity(&self) -> io::Result<IntegrityReport> {
+        self.manifest()?.verify(&self.folder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{info::build_info::LocalBuildInfo, LocalBuild};
+
+    #[test]
+    fn test_manifest() {
+        let dir = std::env::temp_dir().join(format!["blrs-test-{}", uuid::Uuid::new_v4()]);
+        fs::create_dir_all(dir.join("4.3/scripts")).unwrap();
+        fs::write(dir.join("blender"), b"exe").unwrap();
+        fs::write(dir.join("4.3/scripts/startup.py"), b"print()").unwrap();
+
+        let build = LocalBuild {
+            folder: dir.clone(),
+            info: LocalBuildInfo::new(Default::default()),
+        };
+        build.write().unwrap();
+        assert_eq![
+            build.manifest().unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        ];
+
+        let manifest = build.write_manifest(true).unwrap();
+        assert_eq![manifest.files.len(), 2];
+        assert_eq![manifest.files[0].path, "4.3/scripts/startup.py"];
+        assert_eq![manifest.total_size(), 10];
+        assert_eq![build.manifest().unwrap(), manifest];
+        assert![build.verify_integrity().unwrap().is_intact()];
+
+        fs::write(dir.join("4.3/scripts/startup.py"), b"exit()!").unwrap();
+        fs::remove_file(dir.join("blender")).unwrap();
+        fs::write(dir.join("notes.txt"), b"").unwrap();
+        let report = build.verify_integrity().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq![report.modified, ["4.3/scripts/startup.py"]];
+        assert_eq![report.missing, ["blender"]];
+        assert_eq![report.added, ["notes.txt"]];
+    }
+}
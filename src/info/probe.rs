@@ -0,0 +1,223 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use log::debug;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    build_targets::BuildArtifactFormat, extraction::FileExtractor, LocalBuild, RemoteBuild,
+};
+
+use super::{launching::OSLaunchTarget, read_blendfile_header, BlendFileHeader};
+
+/// Extracts the executable of a build archive along with the libraries it needs to run.
+const ARCHIVE_EXE_PATTERN: &str = r"^[^/]+/(blender|blender\.exe|lib/.*|[^/]*\.dll)$";
+
+/// What [`probe_build`] found at a path.
+#[derive(Debug, Clone)]
+pub enum ProbedBuild {
+    /// A build folder with a `.build_info` file.
+    Installed(LocalBuild),
+    /// A build folder or executable without a `.build_info` file, whose info was generated by running Blender.
+    Generated(LocalBuild),
+    /// A build archive. Its info is parsed from the file name, or from running its executable if the
+    /// name has no version. The link is a `file://` URL to the archive.
+    Archive(RemoteBuild),
+    /// A .blend file.
+    BlendFile(BlendFileHeader),
+}
+
+/// Errors from [`probe_build`].
+#[derive(Debug, Error)]
+pub enum ProbeError {
+    /// The path could not be read.
+    #[error("Failed to read {0:?}: {1}")]
+    Io(PathBuf, #[source] io::Error),
+    /// The path is not a build folder, build archive, executable, or .blend file.
+    #[error("{0:?} is not a build, build archive, or .blend file")]
+    Unrecognized(PathBuf),
+    /// The folder has no `.build_info` file and no Blender executable to generate one from.
+    #[error("{0:?} has no build info or Blender executable")]
+    NoExecutable(PathBuf),
+    /// Neither the archive's name nor its executable gave a version.
+    #[error("Could not find the version of {0:?}: {1}")]
+    UnknownArchiveVersion(PathBuf, #[source] io::Error),
+    /// The file looks like a .blend file, but its header could not be read.
+    #[error("Failed to read the .blend header of {0:?}: {1}")]
+    InvalidBlend(PathBuf, #[source] io::Error),
+}
+
+/// Works out what kind of build, if any, is at `path`, for things like dragging a file onto a frontend.
+///
+/// - Folders are read with [`LocalBuild::read`], or [`LocalBuild::generate_from_exe`] if they have no `.build_info`.
+/// - Blender executables are read with [`LocalBuild::generate_from_exe`].
+/// - Archives are parsed with [`RemoteBuild::from_filename`]. If the name has no version, the executable
+///   is extracted to a temporary folder and run instead.
+/// - `.blend` files are read with [`read_blendfile_header`].
+pub fn probe_build(path: &Path) -> Result<ProbedBuild, ProbeError> {
+    let io_err = |e| ProbeError::Io(path.to_path_buf(), e);
+    let metadata = fs::metadata(path).map_err(io_err)?;
+
+    if metadata.is_dir() {
+        return match LocalBuild::read(path) {
+            Ok(build) => Ok(ProbedBuild::Installed(build)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let exe = OSLaunchTarget::try_default()
+                    .map(|t| path.join(t.exe_name()))
+                    .filter(|exe| exe.exists())
+                    .ok_or_else(|| ProbeError::NoExecutable(path.to_path_buf()))?;
+                LocalBuild::generate_from_exe(&exe)
+                    .map(ProbedBuild::Generated)
+                    .map_err(io_err)
+            }
+            Err(e) => Err(io_err(e)),
+        };
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let lower = name.to_lowercase();
+
+    if lower.ends_with(".blend") || lower.trim_end_matches(char::is_numeric).ends_with(".blend") {
+        return read_blendfile_header(path)
+            .map(ProbedBuild::BlendFile)
+            .map_err(|(e, _)| ProbeError::InvalidBlend(path.to_path_buf(), e));
+    }
+
+    if lower == "blender" || lower == "blender.exe" {
+        return LocalBuild::generate_from_exe(path)
+            .map(ProbedBuild::Generated)
+            .map_err(io_err);
+    }
+
+    match BuildArtifactFormat::from_filename(&name) {
+        Some(format) => probe_archive(path, &name, format).map(ProbedBuild::Archive),
+        None => Err(ProbeError::Unrecognized(path.to_path_buf())),
+    }
+}
+
+/// Finds the executable among the files extracted with [`ARCHIVE_EXE_PATTERN`], since the first one
+/// extracted may just as well be a library.
+///
+/// The current OS's executable name is tried first, then the Linux and Windows ones.
+fn find_archive_exe(dest: &Path, extracted: &Path) -> io::Result<PathBuf> {
+    let root = extracted
+        .strip_prefix(dest)
+        .ok()
+        .and_then(|p| p.components().next())
+        .map(|c| dest.join(c))
+        .unwrap_or_else(|| dest.to_path_buf());
+
+    OSLaunchTarget::try_default()
+        .into_iter()
+        .chain([
+            OSLaunchTarget::Linux,
+            OSLaunchTarget::Windows { no_console: false },
+        ])
+        .map(|t| root.join(t.exe_name()))
+        .find(|exe| exe.is_file())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!["No Blender executable in {:?}", root],
+            )
+        })
+}
+
+fn probe_archive(
+    path: &Path,
+    name: &str,
+    format: BuildArtifactFormat,
+) -> Result<RemoteBuild, ProbeError> {
+    let link = Url::from_file_path(path.canonicalize().unwrap_or(path.to_path_buf()))
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| path.to_string_lossy().to_string());
+    let metadata = fs::metadata(path).ok();
+    let file_size = metadata.as_ref().map(|m| m.len());
+    let modified = metadata
+        .and_then(|m| m.modified().ok())
+        .map(DateTime::<Utc>::from);
+
+    if let Some(mut build) = RemoteBuild::from_filename(link.clone(), name) {
+        if let Some(modified) = modified {
+            build.basic.commit_dt = modified;
+        }
+        return Ok(RemoteBuild { file_size, ..build });
+    }
+
+    debug![
+        "{:?} has no version in its name, running its executable",
+        path
+    ];
+    let unknown = |e| ProbeError::UnknownArchiveVersion(path.to_path_buf(), e);
+    let temp = std::env::temp_dir().join(format!["blrs-probe-{}", uuid::Uuid::new_v4()]);
+    let build = FileExtractor::new(path)
+        .and_then(|mut extractor| extractor.extract_single(ARCHIVE_EXE_PATTERN, &temp))
+        .and_then(|extracted| find_archive_exe(&temp, &extracted))
+        .and_then(|exe| LocalBuild::generate_from_exe(&exe));
+    let _ = fs::remove_dir_all(&temp);
+
+    Ok(RemoteBuild {
+        link,
+        basic: build.map_err(unknown)?.info.basic,
+        platform: None,
+        architecture: None,
+        file_extension: Some(format.extension().to_string()),
+        file_size,
+        file_mtime: None,
+        patch_name: None,
+        raw_branch: None,
+        raw: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{find_archive_exe, probe_build, ProbeError, ProbedBuild};
+
+    #[test]
+    fn test_probe_build() {
+        let dir = std::env::temp_dir().join(format!["blrs-test-{}", uuid::Uuid::new_v4()]);
+        fs::create_dir_all(&dir).unwrap();
+
+        let archive = dir.join("blender-4.3.0-linux-x64.tar.xz");
+        fs::write(&archive, b"not really an archive").unwrap();
+        let notes = dir.join("notes.txt");
+        fs::write(&notes, b"").unwrap();
+
+        let archive = probe_build(&archive);
+        let notes = probe_build(&notes);
+        fs::create_dir(dir.join("empty")).unwrap();
+        let empty_folder = probe_build(&dir.join("empty"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert![matches![
+            archive,
+            Ok(ProbedBuild::Archive(b)) if b.basic.ver.to_string().starts_with("4.3.0") && b.file_size == Some(21)
+        ]];
+        assert![matches![notes, Err(ProbeError::Unrecognized(_))]];
+        assert![matches![empty_folder, Err(ProbeError::NoExecutable(_))]];
+    }
+
+    #[test]
+    fn test_find_archive_exe() {
+        let dir = std::env::temp_dir().join(format!["blrs-test-{}", uuid::Uuid::new_v4()]);
+        let root = dir.join("blender-4.3.0-windows-x64");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("BlendThumb.dll"), b"").unwrap();
+        fs::write(root.join("blender.exe"), b"").unwrap();
+
+        let exe = find_archive_exe(&dir, &root.join("BlendThumb.dll"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq![exe.unwrap(), root.join("blender.exe")];
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use crate::repos::{installed_builds, BuildRef, RepoEntry};
+
+use super::blendfile_reader::{read_blendfile_header, BlendReadErr};
+
+/// Suggests installed builds that can open a .blend file, newest first.
+///
+/// The file's header is read to find the version it was saved with, and every installed build
+/// with at least that major and minor version is returned. Compressed .blend files are supported
+/// with the `compressed-blends` feature.
+pub fn suggest_build_for_file<'a>(
+    blend: &Path,
+    repos: &'a [RepoEntry],
+) -> Result<Vec<BuildRef<'a>>, BlendReadErr> {
+    let header = read_blendfile_header(blend)?;
+    let (major, minor) = (header.version.0 as u64, header.version.1 as u64);
+
+    let mut builds: Vec<_> = installed_builds(repos)
+        .filter(|b| {
+            let v = b.build.info.basic.version();
+            (v.major, v.minor) >= (major, minor)
+        })
+        .collect();
+    builds.sort_by(|a, b| b.build.info.basic.cmp(&a.build.info.basic));
+
+    Ok(builds)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::repos::{
+        tests::{entry, installed},
+        RepoEntry,
+    };
+
+    use super::suggest_build_for_file;
+
+    #[test]
+    fn test_suggest_build_for_file() {
+        let blend = std::env::temp_dir().join(format!["blrs-test-{}.blend", uuid::Uuid::new_v4()]);
+        fs::write(&blend, b"BLENDER-v402REND").unwrap();
+
+        let repos = vec![RepoEntry::Unknown(
+            "repo".to_string(),
+            vec![
+                entry(installed("4.1", 20, 1, false)),
+                entry(installed("4.2", 1, 2, false)),
+                entry(installed("4.3", 10, 3, false)),
+            ],
+        )];
+
+        let suggested: Vec<_> = suggest_build_for_file(&blend, &repos)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.folder_name)
+            .collect();
+        assert_eq![suggested, ["4.3", "4.2"]];
+
+        fs::remove_file(blend).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use directories::BaseDirs;
+use log::debug;
+
+use super::LocalBuild;
+
+/// The repo ID system-installed builds are listed under. See [`crate::repos::system_repo_entry`].
+pub const SYSTEM_REPO_ID: &str = "system";
+
+/// Steam's folder name for Blender, inside of `steamapps/common/`.
+const STEAM_APP_FOLDER: &str = "Blender";
+
+/// The Flatpak application ID of Blender.
+const FLATPAK_APP_ID: &str = "org.blender.Blender";
+
+/// Lists the Steam library folders found in `libraryfolders.vdf`, including `steam_root` itself.
+fn steam_libraries(steam_root: &Path) -> Vec<PathBuf> {
+    let mut libraries = vec![steam_root.to_path_buf()];
+
+    let vdf = steam_root.join("steamapps").join("libraryfolders.vdf");
+    if let Ok(s) = fs::read_to_string(vdf) {
+        // Only the `"path"		"..."` lines are needed, so the format isn't fully parsed
+        libraries.extend(s.lines().filter_map(|line| {
+            let mut parts = line.split('"').filter(|p| !p.trim().is_empty());
+            match (parts.next(), parts.next()) {
+                (Some("path"), Some(path)) => Some(PathBuf::from(path.replace("\\\\", "\\"))),
+                _ => None,
+            }
+        }));
+    }
+
+    libraries
+}
+
+/// The places Steam is usually installed to.
+fn steam_roots(base: Option<&BaseDirs>) -> Vec<PathBuf> {
+    let mut roots = vec![];
+    if let Some(base) = base {
+        roots.push(base.home_dir().join(".steam/steam"));
+        roots.push(base.data_dir().join("Steam"));
+        roots.push(base.home_dir().join("Library/Application Support/Steam"));
+    }
+    if cfg!(windows) {
+        roots.push(PathBuf::from(r"C:\Program Files (x86)\Steam"));
+        roots.push(PathBuf::from(r"C:\Program Files\Steam"));
+    }
+    roots
+}
+
+/// Lists the executables of the well-known places Blender gets installed to outside of blrs.
+///
+/// This covers package managers (`/usr/bin`, `/snap/bin`, and anything else on `PATH`), Flatpak exports,
+/// Steam libraries, `/Applications/Blender.app`, and the Blender Foundation's Windows installer.
+/// Only paths that exist are returned, and paths that resolve to the same file are only listed once.
+pub fn system_blender_candidates() -> Vec<PathBuf> {
+    let base = BaseDirs::new();
+    let exe = if cfg!(windows) {
+        "blender.exe"
+    } else {
+        "blender"
+    };
+
+    let mut candidates: Vec<PathBuf> = vec![
+        PathBuf::from("/usr/bin/blender"),
+        PathBuf::from("/usr/local/bin/blender"),
+        PathBuf::from("/snap/bin/blender"),
+        PathBuf::from("/var/lib/flatpak/exports/bin").join(FLATPAK_APP_ID),
+        PathBuf::from("/Applications/Blender.app/Contents/MacOS/Blender"),
+    ];
+    candidates.extend(which::which_all(exe).into_iter().flatten());
+    if let Some(base) = &base {
+        candidates.push(
+            base.data_dir()
+                .join("flatpak/exports/bin")
+                .join(FLATPAK_APP_ID),
+        );
+        candidates.push(
+            base.home_dir()
+                .join("Applications/Blender.app/Contents/MacOS/Blender"),
+        );
+    }
+    candidates.extend(
+        steam_roots(base.as_ref())
+            .iter()
+            .flat_map(|root| steam_libraries(root))
+            .map(|lib| lib.join("steamapps/common").join(STEAM_APP_FOLDER))
+            .flat_map(|folder| {
+                [
+                    folder.join(exe),
+                    folder.join("Blender.app/Contents/MacOS/Blender"),
+                ]
+            }),
+    );
+    if cfg!(windows) {
+        // The installer puts every version into its own "Blender X.Y" folder
+        if let Ok(entries) = fs::read_dir(r"C:\Program Files\Blender Foundation") {
+            candidates.extend(entries.flatten().map(|e| e.path().join(exe)));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|p| p.is_file())
+        .filter(|p| seen.insert(fs::canonicalize(p).unwrap_or_else(|_| p.clone())))
+        .collect()
+}
+
+/// Detects Blender installations that were not installed by blrs, such as ones from Steam, a distro's
+/// package manager, Flatpak, or the official installers.
+///
+/// Every candidate from [`system_blender_candidates`] is run to collect its build info, so this can take
+/// a while. Executables that fail to report their info are skipped.
+/// The returned builds point directly at their executables through [`LocalBuildInfo::custom_exe`], and
+/// do not have a `.build_info` file, so they should not be written.
+///
+/// [`LocalBuildInfo::custom_exe`]: super::build_info::LocalBuildInfo::custom_exe
+pub fn detect_system_blenders() -> Vec<LocalBuild> {
+    system_blender_candidates()
+        .into_iter()
+        .filter_map(|exe| match LocalBuild::generate_from_exe(&exe) {
+            Ok(mut build) => {
+                build.info.custom_exe = exe.file_name().map(|n| n.to_string_lossy().to_string());
+                Some(build)
+            }
+            Err(e) => {
+                debug!["Skipping {:?}: {}", exe, e];
+                None
+            }
+        })
+        .collect()
+}
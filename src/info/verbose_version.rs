@@ -8,6 +8,77 @@ use std::fmt::Display;
 
 use semver::Version;
 
+/// The branch stored in a [`VerboseVersion`] when the real branch is unknown.
+pub const NULL_BRANCH: &str = "null";
+
+/// The build hash stored in a [`VerboseVersion`] when the real hash is unknown.
+pub const NULL_HASH: &str = "ffffffff";
+
+/// The shortest hash prefix that [`hashes_match`] accepts, matching git's default short hash length.
+pub const MIN_HASH_PREFIX: usize = 7;
+
+/// Compares two commit hashes, allowing either one to be a shortened form of the other.
+///
+/// Hashes are compared case-insensitively. When their lengths differ, the shorter one must be at
+/// least [`MIN_HASH_PREFIX`] characters long to avoid false positives.
+///
+/// ```
+/// use blrs::info::hashes_match;
+///
+/// assert![hashes_match("ddc9f92777cd", "ddc9f92777cd")];
+/// assert![hashes_match("ddc9f92", "ddc9f92777cd4f5e0b7a1c2d3e4f5a6b7c8d9e0f")];
+/// assert![hashes_match("DDC9F92777CD", "ddc9f92")];
+/// assert![!hashes_match("ddc9", "ddc9f92777cd")];
+/// assert![!hashes_match("ddc9f93", "ddc9f92777cd")];
+/// ```
+pub fn hashes_match(a: &str, b: &str) -> bool {
+    let (short, long) = match a.len() <= b.len() {
+        true => (a, b),
+        false => (b, a),
+    };
+
+    if short.len() == long.len() {
+        short.eq_ignore_ascii_case(long)
+    } else {
+        short.len() >= MIN_HASH_PREFIX
+            && long
+                .get(..short.len())
+                .is_some_and(|l| l.eq_ignore_ascii_case(short))
+    }
+}
+
+/// Maps the many spellings of well-known branches to a canonical name: `stable`, `lts`, `daily`,
+/// `experimental`, or `patch`. Branches that aren't recognized return `None`, and should be kept as they are.
+///
+/// Feeds name the same branch differently, so this makes grouping and filtering by branch behave
+/// the same across sources. Names are compared ignoring case and surrounding whitespace.
+///
+/// ```
+/// use blrs::info::normalize_branch;
+///
+/// assert_eq![normalize_branch("release"), Some("stable")];
+/// assert_eq![normalize_branch("v4.2-release"), Some("stable")];
+/// assert_eq![normalize_branch("blender-v4.2-release"), Some("stable")];
+/// assert_eq![normalize_branch("v3.6-lts"), Some("lts")];
+/// assert_eq![normalize_branch("Main"), Some("daily")];
+/// assert_eq![normalize_branch("geometry-nodes"), None];
+/// ```
+pub fn normalize_branch(raw: &str) -> Option<&'static str> {
+    let branch = raw.trim().to_ascii_lowercase();
+    let branch = branch.strip_prefix("blender-").unwrap_or(&branch);
+
+    match branch {
+        "stable" | "release" | "releases" => Some("stable"),
+        "lts" => Some("lts"),
+        "daily" | "nightly" | "main" | "master" => Some("daily"),
+        "experimental" | "exp" => Some("experimental"),
+        "patch" | "patches" | "pr" => Some("patch"),
+        b if b.ends_with("-lts") => Some("lts"),
+        b if b.ends_with("-release") => Some("stable"),
+        _ => None,
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 /// A struct representing a version number with additional information about the build and branch.
 pub struct VerboseVersion {
@@ -23,15 +94,47 @@ impl Default for VerboseVersion {
 }
 
 impl Display for VerboseVersion {
+    /// Writes the version like semver does, leaving out the branch and hash when they are unknown.
+    ///
+    /// ```
+    /// use blrs::info::VerboseVersion;
+    ///
+    /// assert_eq![VerboseVersion::new(4, 3, 0, None, Some("main"), Some("abc1234")).to_string(), "4.3.0+main.abc1234"];
+    /// assert_eq![VerboseVersion::new(4, 3, 0, Some("alpha"), Some("main"), None).to_string(), "4.3.0-alpha+main"];
+    /// assert_eq![VerboseVersion::new(4, 3, 0, None, None, None).to_string(), "4.3.0"];
+    /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write![f, "{}", self.v]
+        write![f, "{}", self.display_short()]?;
+        match (self.has_known_branch(), self.has_known_hash()) {
+            (true, true) => write![f, "+{}", self.v.build],
+            (true, false) => write![f, "+{}", self.branch()],
+            (false, true) => write![f, "+{}", self.build_hash()],
+            (false, false) => Ok(()),
+        }
     }
 }
 
 impl From<Version> for VerboseVersion {
+    /// Splits the version's build metadata into the branch and the build hash.
+    ///
+    /// The hash is everything after the last `.`, so branches with dots in them are kept whole.
+    /// Metadata without a `.` is treated as unknown, see [`NULL_BRANCH`] and [`NULL_HASH`].
+    ///
+    /// ```
+    /// use blrs::info::VerboseVersion;
+    /// use semver::Version;
+    ///
+    /// let v = VerboseVersion::from(Version::parse("4.2.0-alpha+main.PR109522.f723782e3a8c").unwrap());
+    /// assert_eq![(v.branch(), v.build_hash()), ("main.PR109522", "f723782e3a8c")];
+    ///
+    /// let v = VerboseVersion::from(Version::parse("4.2.0-alpha+main-PR109522.f723782e3a8c").unwrap());
+    /// assert_eq![(v.branch(), v.build_hash()), ("main-PR109522", "f723782e3a8c")];
+    /// ```
     fn from(value: Version) -> Self {
-        // Split the build metadata into the build and hash
-        let (build, hash) = value.build.split_once('.').unwrap_or(("null", "ffffffff"));
+        let (build, hash) = value
+            .build
+            .rsplit_once('.')
+            .unwrap_or((NULL_BRANCH, NULL_HASH));
         let hash_split = build.len();
         let metadata = BuildMetadata::new(&format!["{}.{}", build, hash]).unwrap_or_default();
 
@@ -58,8 +161,8 @@ impl VerboseVersion {
         let pre = pre
             .and_then(|p| Prerelease::new(p).ok())
             .unwrap_or_default();
-        let build = build.unwrap_or("null");
-        let hash = hash.unwrap_or("ffffffff");
+        let build = build.unwrap_or(NULL_BRANCH);
+        let hash = hash.unwrap_or(NULL_HASH);
 
         let hash_split = build.len();
 
@@ -92,10 +195,119 @@ impl VerboseVersion {
         &self.v.build[self.hash_split + 1..]
     }
 
+    /// The branch's canonical name from [`normalize_branch`], or the branch itself if it isn't a well-known one.
+    pub fn canonical_branch(&self) -> &str {
+        normalize_branch(self.branch()).unwrap_or(self.branch())
+    }
+
+    /// Checks whether the branch is `branch`, either exactly or by their canonical names (see [`normalize_branch`]).
+    ///
+    /// ```
+    /// use blrs::info::VerboseVersion;
+    ///
+    /// let v = VerboseVersion::new(4, 2, 0, None, Some("v4.2-release"), None);
+    /// assert![v.matches_branch("v4.2-release")];
+    /// assert![v.matches_branch("stable")];
+    /// assert![!v.matches_branch("daily")];
+    /// ```
+    pub fn matches_branch(&self, branch: &str) -> bool {
+        self.branch() == branch || normalize_branch(branch) == Some(self.canonical_branch())
+    }
+
+    /// Whether the branch is known, and not the [`NULL_BRANCH`] placeholder.
+    pub fn has_known_branch(&self) -> bool {
+        self.branch() != NULL_BRANCH
+    }
+
+    /// Whether the build hash is known, and not the [`NULL_HASH`] placeholder.
+    ///
+    /// ```
+    /// use blrs::info::VerboseVersion;
+    ///
+    /// assert![!VerboseVersion::default().has_known_hash()];
+    /// assert![VerboseVersion::new(4, 3, 0, None, None, Some("abc1234")).has_known_hash()];
+    /// ```
+    pub fn has_known_hash(&self) -> bool {
+        self.build_hash() != NULL_HASH
+    }
+
+    /// Alias of [`VerboseVersion::has_known_branch`].
+    #[deprecated(note = "renamed to `has_known_branch`")]
+    pub fn has_real_branch(&self) -> bool {
+        self.has_known_branch()
+    }
+
+    /// Alias of [`VerboseVersion::has_known_hash`].
+    #[deprecated(note = "renamed to `has_known_hash`")]
+    pub fn has_real_hash(&self) -> bool {
+        self.has_known_hash()
+    }
+
+    /// Checks whether the build hash matches `hash`, which may be a shortened or full form. See [`hashes_match`].
+    ///
+    /// An unknown hash never matches, even a query for [`NULL_HASH`] itself.
+    pub fn matches_hash(&self, hash: &str) -> bool {
+        self.has_known_hash() && hashes_match(self.build_hash(), hash)
+    }
+
+    /// The version without the branch and build hash, such as `4.3.0` or `4.3.0-alpha`.
+    ///
+    /// ```
+    /// use blrs::info::VerboseVersion;
+    ///
+    /// let v = VerboseVersion::new(4, 3, 0, Some("alpha"), Some("daily"), Some("abc123"));
+    /// assert_eq![v.display_short(), "4.3.0-alpha"];
+    /// ```
+    pub fn display_short(&self) -> String {
+        let mut s = format!["{}.{}.{}", self.v.major, self.v.minor, self.v.patch];
+        if !self.v.pre.is_empty() {
+            s = format!["{}-{}", s, self.v.pre];
+        }
+        s
+    }
+
+    /// The version followed by the branch and build hash, such as `4.3.0 (daily, abc123)`.
+    ///
+    /// ```
+    /// use blrs::info::VerboseVersion;
+    ///
+    /// let v = VerboseVersion::new(4, 3, 0, None, Some("daily"), Some("abc123"));
+    /// assert_eq![v.display_verbose(), "4.3.0 (daily, abc123)"];
+    /// ```
+    pub fn display_verbose(&self) -> String {
+        format![
+            "{} ({}, {})",
+            self.display_short(),
+            self.branch(),
+            self.build_hash()
+        ]
+    }
+
+    /// A label made of the branch and prerelease exactly as they are stored, separated by a space.
+    ///
+    /// Unlike title-cased labels, names like `v43` or `lts` are kept as-is. An unknown branch
+    /// (see [`VerboseVersion::has_known_branch`]) and an empty prerelease are left out.
+    ///
+    /// ```
+    /// use blrs::info::VerboseVersion;
+    ///
+    /// let v = VerboseVersion::new(4, 3, 0, Some("alpha"), Some("daily"), None);
+    /// assert_eq![v.label_plain(), "daily alpha"];
+    /// let v = VerboseVersion::new(4, 2, 0, None, Some("lts"), None);
+    /// assert_eq![v.label_plain(), "lts"];
+    /// assert_eq![VerboseVersion::default().label_plain(), ""];
+    /// ```
+    pub fn label_plain(&self) -> String {
+        let branch = self.has_known_branch().then(|| self.branch());
+        let pre = (!self.v.pre.is_empty()).then(|| self.v.pre.as_str());
+
+        branch.into_iter().chain(pre).collect::<Vec<_>>().join(" ")
+    }
+
     /// Updates the VerboseVersion with a provided branch, returning an Ok result containing the updated version.
     /// Returns an error if the branch cannot be parsed as valid.
     pub fn with_branch(self, branch: Option<&str>) -> Result<Self, semver::Error> {
-        let branch = branch.unwrap_or("null");
+        let branch = branch.unwrap_or(NULL_BRANCH);
         let hash_split = branch.len();
 
         Ok(Self {
@@ -110,7 +322,7 @@ impl VerboseVersion {
     /// Updates the VerboseVersion with a provided build hash, returning an Ok result containing the updated version.
     /// Returns an error if the hash cannot be parsed as valid.
     pub fn with_build_hash(self, hash: Option<&str>) -> Result<Self, semver::Error> {
-        let hash = hash.unwrap_or("ffffffff");
+        let hash = hash.unwrap_or(NULL_HASH);
 
         Ok(Self {
             v: Version {
@@ -0,0 +1,401 @@
+#[cfg(feature = "disk-space")]
+mod disk_space;
+
+#[cfg(feature = "disk-space")]
+#[cfg_attr(docsrs, doc(cfg(feature = "disk-space")))]
+pub use disk_space::available_space;
+
+mod events;
+mod installer;
+mod lock;
+#[cfg(feature = "signature-check")]
+mod signature;
+pub use events::InstallEvent;
+pub use installer::unpack_installer;
+pub use lock::{LibraryLock, LOCKFILE_NAME};
+#[cfg(feature = "signature-check")]
+#[cfg_attr(docsrs, doc(cfg(feature = "signature-check")))]
+pub use signature::{verify_signature, SignatureInfo, VerifyError, BLENDER_SIGNER};
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use futures_util::{stream, StreamExt};
+use log::debug;
+use reqwest::{Client, Url};
+use thiserror::Error;
+
+#[cfg(feature = "signature-check")]
+use crate::info::launching::OSLaunchTarget;
+use crate::{
+    build_targets::BuildArtifactFormat,
+    extraction::FileExtractor,
+    fetching::{
+        build_repository::{BuildRepo, FetchError},
+        fetcher::FetchStreamerState,
+    },
+    info::build_info::LocalBuildInfo,
+    BLRSPaths, LocalBuild, RemoteBuild,
+};
+
+/// How much space an archive needs to be extracted, as a multiple of its size.
+/// Blender archives usually expand to around 3 times their size.
+pub const EXTRACTION_HEADROOM: u64 = 3;
+
+/// Errors that can occur while installing a build.
+#[derive(Debug, Error)]
+pub enum InstallError {
+    /// There is not enough free space in the library to download and extract the build.
+    #[error(
+        "Not enough free space: {needed} bytes are needed, but only {available} are available"
+    )]
+    InsufficientSpace {
+        /// The amount of bytes the install needs.
+        needed: u64,
+        /// The amount of bytes available in the library.
+        available: u64,
+    },
+    /// Another process is holding the library's lock.
+    #[error("The library is locked by another process ({0:?})")]
+    LibraryLocked(PathBuf),
+    /// The build could not be downloaded.
+    #[error("Failed to download the build: {0}")]
+    Fetch(FetchError),
+    /// An IO error occurred while writing or extracting the build.
+    #[error("IO error while installing the build: {0}")]
+    Io(#[from] io::Error),
+    /// The build's signature is missing, invalid, or not from the Blender Foundation.
+    #[cfg(feature = "signature-check")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "signature-check")))]
+    #[error("Failed to verify the build's signature: {0}")]
+    Signature(#[from] VerifyError),
+}
+
+impl From<FetchError> for InstallError {
+    fn from(value: FetchError) -> Self {
+        InstallError::Fetch(value)
+    }
+}
+
+/// Estimates the amount of bytes needed to install a build whose download is `size` bytes large.
+///
+/// Archives need room for both the download and the extracted files.
+pub fn required_space(build: &RemoteBuild, size: u64) -> u64 {
+    match build.format() {
+        Some(BuildArtifactFormat::Dmg | BuildArtifactFormat::Exe) => size,
+        _ => size + size * EXTRACTION_HEADROOM,
+    }
+}
+
+/// Checks that the filesystem holding `dir` has at least `needed` bytes available.
+#[cfg(feature = "disk-space")]
+#[cfg_attr(docsrs, doc(cfg(feature = "disk-space")))]
+pub fn check_free_space(dir: &Path, needed: u64) -> Result<(), InstallError> {
+    match available_space(dir)? {
+        Some(available) if available < needed => {
+            Err(InstallError::InsufficientSpace { needed, available })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Streams the build's file into `target`.
+async fn download<F>(
+    client: Client,
+    url: Url,
+    build: &RemoteBuild,
+    target: &Path,
+    on_event: &mut F,
+) -> Result<(), InstallError>
+where
+    F: FnMut(InstallEvent),
+{
+    let mut file = File::create(target)?;
+    let mut state = FetchStreamerState::new(client, url);
+    let mut downloaded = 0;
+
+    loop {
+        state = state.advance().await;
+        match &state {
+            FetchStreamerState::Downloading {
+                response,
+                last_chunk,
+            } => {
+                file.write_all(last_chunk)?;
+                downloaded += last_chunk.len() as u64;
+                on_event(InstallEvent::Downloading {
+                    downloaded,
+                    total: build.file_size.or(response.content_length()),
+                });
+            }
+            FetchStreamerState::Finished { response } => {
+                if !response.status().is_success() {
+                    return Err(FetchError::ReturnCode(
+                        response.status(),
+                        response.status().canonical_reason(),
+                    )
+                    .into());
+                }
+                break;
+            }
+            FetchStreamerState::Err(_) => break,
+            FetchStreamerState::Ready(_, _) => unreachable!(),
+        }
+    }
+
+    match state {
+        FetchStreamerState::Err(e) => Err(FetchError::Reqwest(e).into()),
+        _ => Ok(file.flush()?),
+    }
+}
+
+/// Downloads a remote build, extracts it into the library, and writes its `.build_info`.
+///
+/// The build is installed to `<library>/<repo_id>/<full_version>`, see [`BLRSPaths::install_path_for`].
+/// It is downloaded and extracted in its own folder in [`BLRSPaths::staging`] first, and only moved
+/// into place once it is complete, so a failed install leaves nothing behind in the library.
+/// Windows `.msi` installers are unpacked with [`unpack_installer`] instead of being extracted.
+/// With the `disk-space` feature, the install refuses to start if the library does not have
+/// enough free space for the download and its extracted files (see [`required_space`]).
+///
+/// `on_event` receives an [`InstallEvent`] for every stage of the install, which can be
+/// forwarded to a channel to drive a progress display.
+///
+/// The library is locked with a [`LibraryLock`] for the duration of the install, and
+/// [`InstallError::LibraryLocked`] is returned if another process is already holding it.
+///
+/// With the `build-manifest` feature, a [`crate::info::Manifest`] of the build's files is written
+/// next to its `.build_info`, see [`LocalBuild::verify_integrity`].
+///
+/// With the `signature-check` feature, builds for macOS and Windows are only installed if they are
+/// signed by the Blender Foundation, see `verify_signature`.
+pub async fn install_build<F>(
+    client: Client,
+    build: &RemoteBuild,
+    repo: &BuildRepo,
+    paths: &BLRSPaths,
+    on_event: F,
+) -> Result<LocalBuild, InstallError>
+where
+    F: FnMut(InstallEvent),
+{
+    let _lock = LibraryLock::acquire(paths)?;
+    install_locked(client, build, repo, paths, on_event).await
+}
+
+/// Installs a build, assuming the library's lock is already held.
+async fn install_locked<F>(
+    client: Client,
+    build: &RemoteBuild,
+    repo: &BuildRepo,
+    paths: &BLRSPaths,
+    mut on_event: F,
+) -> Result<LocalBuild, InstallError>
+where
+    F: FnMut(InstallEvent),
+{
+    let repo_dir = paths.path_to_repo(repo);
+    fs::create_dir_all(&repo_dir)?;
+    let staging = paths.staging.join(uuid::Uuid::new_v4().to_string());
+    fs::create_dir_all(&staging)?;
+
+    let result = install_staged(client, build, repo, paths, &staging, &mut on_event).await;
+
+    let _ = fs::remove_dir_all(&staging);
+    if result.is_ok() {
+        on_event(InstallEvent::Done);
+    }
+    result
+}
+
+/// Downloads and extracts the build into its own `staging` folder, and moves the finished build into the library.
+async fn install_staged<F>(
+    client: Client,
+    build: &RemoteBuild,
+    repo: &BuildRepo,
+    paths: &BLRSPaths,
+    staging: &Path,
+    on_event: &mut F,
+) -> Result<LocalBuild, InstallError>
+where
+    F: FnMut(InstallEvent),
+{
+    let url = build
+        .try_url()
+        .map_err(|e| FetchError::InvalidUrl(build.link.clone(), e))?;
+
+    #[cfg(feature = "disk-space")]
+    {
+        on_event(InstallEvent::CheckingSpace);
+        let size = match build.file_size {
+            Some(size) => Some(size),
+            None => build.remote_size(client.clone()).await?,
+        };
+        if let Some(size) = size {
+            let needed = required_space(build, size);
+            check_free_space(staging, needed)?;
+            // Only the extracted build ends up in the library
+            check_free_space(&paths.path_to_repo(repo), needed - size)?;
+        }
+    }
+
+    let filename = url
+        .path_segments()
+        .and_then(|mut s| s.next_back())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("download")
+        .to_string();
+    let archive = staging.join(filename);
+
+    debug!["Downloading {} to {:?}", build.link, archive];
+    download(client, url.clone(), build, &archive, on_event).await?;
+    let local = extract(build, &archive, &staging.join("extracted"), repo, on_event)?;
+
+    let target = paths.install_path_for(&repo.repo_id, build);
+    debug!["Moving {:?} to {:?}", local.folder, target];
+    move_dir(&local.folder, &target)?;
+
+    Ok(LocalBuild {
+        folder: target,
+        ..local
+    })
+}
+
+/// Extracts the archive into `dest` and writes the build's `.build_info`, returning the extracted build.
+fn extract<F>(
+    build: &RemoteBuild,
+    archive: &Path,
+    dest: &Path,
+    repo: &BuildRepo,
+    on_event: &mut F,
+) -> Result<LocalBuild, InstallError>
+where
+    F: FnMut(InstallEvent),
+{
+    let root = match build.format() {
+        Some(format) if format.is_installer() => {
+            on_event(InstallEvent::Extracting { done: 0, total: 1 });
+            let root = unpack_installer(archive, dest)?;
+            on_event(InstallEvent::Extracting { done: 1, total: 1 });
+            root
+        }
+        _ => FileExtractor::new(archive)?.extract_to_with_progress(dest, |done, total| {
+            on_event(InstallEvent::Extracting { done, total })
+        })?,
+    };
+    let local = LocalBuild {
+        folder: root,
+        info: LocalBuildInfo {
+            source_repo: Some(repo.repo_id.clone()),
+            source_link: Some(build.link.clone()),
+            ..LocalBuildInfo::new(build.basic.clone())
+        },
+    };
+
+    #[cfg(feature = "signature-check")]
+    if let Some(os) = OSLaunchTarget::try_default().filter(|os| *os != OSLaunchTarget::Linux) {
+        on_event(InstallEvent::VerifyingSignature);
+        let info = verify_signature(&local.find_executable(&os), &os)?;
+        if !info.is_blender_foundation() {
+            return Err(VerifyError::UnexpectedSigner(info).into());
+        }
+    }
+
+    on_event(InstallEvent::WritingMetadata);
+    local.write()?;
+
+    #[cfg(feature = "build-manifest")]
+    {
+        on_event(InstallEvent::WritingManifest);
+        local.write_manifest(true)?;
+    }
+
+    Ok(local)
+}
+
+/// Renames `from` to `to`, falling back to copying when they are on different filesystems.
+///
+/// A copy is made next to `to` first and renamed into place, so the library never holds a partial build.
+fn move_dir(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            let parent = to.parent().unwrap_or(to);
+            let partial = parent.join(format![".{}.partial", uuid::Uuid::new_v4()]);
+            let result = copy_dir(from, &partial).and_then(|_| fs::rename(&partial, to));
+            if result.is_err() {
+                let _ = fs::remove_dir_all(&partial);
+            }
+            result
+        }
+        r => r,
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let (src, dest) = (entry.path(), to.join(entry.file_name()));
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            copy_dir(&src, &dest)?;
+        } else if file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(fs::read_link(&src)?, &dest)?;
+            #[cfg(not(unix))]
+            fs::copy(&src, &dest).map(|_| ())?;
+        } else {
+            fs::copy(&src, &dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Installs several builds at once, running at most `max_concurrent` installs at a time.
+///
+/// Each install is independent of the others, so one failing does not stop the rest.
+/// The results are returned in the same order as `builds`.
+pub async fn install_many(
+    client: Client,
+    builds: &[(&BuildRepo, &RemoteBuild)],
+    paths: &BLRSPaths,
+    max_concurrent: usize,
+) -> Vec<Result<LocalBuild, InstallError>> {
+    // The lock is taken once for the whole batch, as the installs run within the same process
+    let _lock = match LibraryLock::acquire(paths) {
+        Ok(lock) => lock,
+        Err(e) => {
+            return builds
+                .iter()
+                .map(|_| {
+                    Err(match &e {
+                        InstallError::LibraryLocked(p) => InstallError::LibraryLocked(p.clone()),
+                        e => InstallError::Io(io::Error::other(e.to_string())),
+                    })
+                })
+                .collect()
+        }
+    };
+
+    let mut results: Vec<_> = stream::iter(builds.iter().enumerate())
+        .map(|(idx, (repo, build))| {
+            let client = client.clone();
+            async move {
+                (
+                    idx,
+                    install_locked(client, build, repo, paths, |_| {}).await,
+                )
+            }
+        })
+        .buffer_unordered(max_concurrent.max(1))
+        .collect()
+        .await;
+
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, r)| r).collect()
+}
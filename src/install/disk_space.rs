@@ -0,0 +1,65 @@
+use std::{io, path::Path};
+
+/// Returns the amount of bytes available to the current user on the filesystem holding `path`.
+///
+/// `path` has to exist. Returns `Ok(None)` on platforms where free space cannot be queried.
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> io::Result<Option<u64>> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is a valid nul-terminated string and `stat` is a valid buffer to write into.
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // The field types differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+/// Returns the amount of bytes available to the current user on the filesystem holding `path`.
+///
+/// `path` has to exist. Returns `Ok(None)` on platforms where free space cannot be queried.
+#[cfg(windows)]
+pub fn available_space(path: &Path) -> io::Result<Option<u64>> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory_name: *const u16,
+            free_bytes_available_to_caller: *mut u64,
+            total_number_of_bytes: *mut u64,
+            total_number_of_free_bytes: *mut u64,
+        ) -> i32;
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+    let mut available = 0u64;
+    // SAFETY: `wide` is nul-terminated and the output pointers are either valid or null.
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(Some(available))
+}
+
+/// Returns the amount of bytes available to the current user on the filesystem holding `path`.
+///
+/// `path` has to exist. Returns `Ok(None)` on platforms where free space cannot be queried.
+#[cfg(not(any(unix, windows)))]
+pub fn available_space(_path: &Path) -> io::Result<Option<u64>> {
+    Ok(None)
+}
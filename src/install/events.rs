@@ -0,0 +1,57 @@
+/// A stage of [`super::install_build`], reported as the install progresses.
+///
+/// Events arrive in lifecycle order: `CheckingSpace` (only with the `disk-space` feature), then any
+/// number of `Downloading` events, then `Extracting` events, then `VerifyingSignature`, then
+/// `WritingMetadata`, then `WritingManifest`, and finally `Done` if the install succeeded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallEvent {
+    /// Free space is being checked before the download starts.
+    CheckingSpace,
+    /// The build is being downloaded.
+    Downloading {
+        /// The amount of bytes downloaded so far.
+        downloaded: u64,
+        /// The size of the download, if known.
+        total: Option<u64>,
+    },
+    /// The downloaded archive is being extracted.
+    ///
+    /// Progress is measured the same way as [`crate::extraction::FileExtractor::extract_to_with_progress`].
+    Extracting {
+        /// The amount processed so far.
+        done: u64,
+        /// The total amount to process.
+        total: u64,
+    },
+    /// The build's code signature is being checked. Only sent with the `signature-check` feature,
+    /// on macOS and Windows.
+    VerifyingSignature,
+    /// The `.build_info` file is being written.
+    WritingMetadata,
+    /// A manifest of the build's files is being written. Only sent with the `build-manifest` feature.
+    WritingManifest,
+    /// The build has been moved into the library.
+    Done,
+}
+
+impl InstallEvent {
+    /// The progress of the current stage, from `0.0` to `1.0`, if it can be measured.
+    ///
+    /// ```
+    /// use blrs::install::InstallEvent;
+    ///
+    /// let event = InstallEvent::Downloading { downloaded: 40, total: Some(100) };
+    /// assert_eq![event.fraction(), Some(0.4)];
+    /// assert_eq![InstallEvent::WritingMetadata.fraction(), None];
+    /// ```
+    pub fn fraction(&self) -> Option<f64> {
+        match self {
+            Self::Downloading {
+                downloaded,
+                total: Some(total),
+            } if *total > 0 => Some(*downloaded as f64 / *total as f64),
+            Self::Extracting { done, total } if *total > 0 => Some(*done as f64 / *total as f64),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(windows)]
+use std::fs;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::build_targets::BuildArtifactFormat;
+
+/// Finds the folder holding `blender.exe` under `root`, searching breadth first.
+#[cfg(windows)]
+fn find_blender_folder(root: &Path) -> Option<PathBuf> {
+    let mut queue = std::collections::VecDeque::from([root.to_path_buf()]);
+    while let Some(dir) = queue.pop_front() {
+        if dir.join("blender.exe").is_file() {
+            return Some(dir);
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                queue.push_back(entry.path());
+            }
+        }
+    }
+
+    None
+}
+
+/// Unpacks a Windows `.msi` installer into `dest` without installing it system-wide.
+///
+/// This runs an administrative install (`msiexec /a`), which only copies the installer's files
+/// into the target folder. Returns the folder holding `blender.exe`.
+#[cfg(windows)]
+pub fn unpack_msi(msi: &Path, dest: &Path) -> io::Result<PathBuf> {
+    use std::process::Command;
+
+    fs::create_dir_all(dest)?;
+    let status = Command::new("msiexec")
+        .arg("/a")
+        .arg(msi)
+        .arg("/qn")
+        .arg(format!["TARGETDIR={}", dest.display()])
+        .status()?;
+
+    if !status.success() {
+        return Err(io::Error::other(format![
+            "msiexec failed to unpack {:?}: {}",
+            msi, status
+        ]));
+    }
+
+    // The administrative install keeps a copy of the msi, which is not needed
+    if let Some(name) = msi.file_name() {
+        let _ = fs::remove_file(dest.join(name));
+    }
+
+    find_blender_folder(dest).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!["{:?} does not contain blender.exe", msi],
+        )
+    })
+}
+
+/// Unpacks a Windows `.msi` installer into `dest` without installing it system-wide.
+///
+/// `.msi` installers can only be unpacked on Windows, so this always returns an [`io::ErrorKind::Unsupported`] error.
+#[cfg(not(windows))]
+pub fn unpack_msi(msi: &Path, _dest: &Path) -> io::Result<PathBuf> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!["{:?} can only be unpacked on Windows", msi],
+    ))
+}
+
+/// Unpacks an installer into `dest`, returning the folder the build ended up in.
+///
+/// Only `.msi` installers are supported. `.exe` installers cannot be unpacked without
+/// installing them system-wide, so they return an [`io::ErrorKind::Unsupported`] error.
+pub fn unpack_installer(installer: &Path, dest: &Path) -> io::Result<PathBuf> {
+    let format = installer
+        .file_name()
+        .and_then(|n| BuildArtifactFormat::from_filename(&n.to_string_lossy()));
+
+    match format {
+        Some(BuildArtifactFormat::Msi) => unpack_msi(installer, dest),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!["{:?} cannot be unpacked without installing it", installer],
+        )),
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    path::{Path, PathBuf},
+};
+
+use log::debug;
+
+use crate::BLRSPaths;
+
+use super::InstallError;
+
+/// The name of the lockfile kept in the library root.
+pub const LOCKFILE_NAME: &str = ".blrs.lock";
+
+/// An advisory lock over the library, held by one process at a time.
+///
+/// Mutating operations like [`super::install_build`] take this lock, so two frontends
+/// installing into the same library cannot race on folder renames or `.build_info` writes.
+/// Read-only operations do not need it. The lock is released when this is dropped.
+#[derive(Debug)]
+pub struct LibraryLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl LibraryLock {
+    /// Tries to lock the library, without waiting.
+    ///
+    /// Returns [`InstallError::LibraryLocked`] if another process holds the lock.
+    pub fn acquire(paths: &BLRSPaths) -> Result<Self, InstallError> {
+        fs::create_dir_all(&paths.library)?;
+        let path = paths.library.join(LOCKFILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+
+        match file.try_lock() {
+            Ok(()) => {
+                debug!["Locked {:?}", path];
+                Ok(Self { file, path })
+            }
+            Err(TryLockError::WouldBlock) => Err(InstallError::LibraryLocked(path)),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
+    /// The path to the lockfile.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for LibraryLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+        debug!["Unlocked {:?}", self.path];
+    }
+}
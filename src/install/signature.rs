@@ -0,0 +1,178 @@
+use std::{
+    io,
+    path::Path,
+    process::{Command, Output},
+};
+
+use thiserror::Error;
+
+use crate::info::launching::OSLaunchTarget;
+
+/// The name the Blender Foundation signs its builds with.
+pub const BLENDER_SIGNER: &str = "Stichting Blender Foundation";
+
+/// Who signed an executable, as found by [`verify_signature`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureInfo {
+    /// The common name of the signing certificate, like [`BLENDER_SIGNER`].
+    pub signer: String,
+    /// The full chain of authorities, starting with the signing certificate.
+    /// On Windows, this only holds the signing certificate's subject.
+    pub authorities: Vec<String>,
+    /// The Apple developer team that signed the app. Only found on macOS.
+    pub team_id: Option<String>,
+}
+
+impl SignatureInfo {
+    /// Whether the build was signed by the Blender Foundation.
+    pub fn is_blender_foundation(&self) -> bool {
+        self.signer == BLENDER_SIGNER
+    }
+}
+
+/// Errors from [`verify_signature`].
+#[derive(Debug, Error)]
+pub enum VerifyError {
+    /// Signatures can't be checked for this OS.
+    #[error("Signatures can not be verified on {0}")]
+    Unsupported(OSLaunchTarget),
+    /// The tool used to check signatures could not be run.
+    #[error("Failed to run {0}: {1}")]
+    Tool(&'static str, #[source] io::Error),
+    /// The file is not signed, or its signature is invalid. Holds the output of the tool.
+    #[error("The signature is missing or invalid: {0}")]
+    Invalid(String),
+    /// The file is validly signed, but not by the expected signer.
+    #[error("Signed by {:?} instead of {:?}", .0.signer, BLENDER_SIGNER)]
+    UnexpectedSigner(SignatureInfo),
+}
+
+/// Checks that the executable or app bundle at `path` has a valid code signature, and returns who signed it.
+///
+/// - macOS: `codesign --verify --deep --strict`, then `codesign -dv` to read the signer.
+/// - Windows: PowerShell's `Get-AuthenticodeSignature`, which checks the file with `WinVerifyTrust`.
+///
+/// Linux builds aren't signed, so checking them returns [`VerifyError::Unsupported`].
+/// This does not check who the signer is, see [`SignatureInfo::is_blender_foundation`].
+pub fn verify_signature(path: &Path, os: &OSLaunchTarget) -> Result<SignatureInfo, VerifyError> {
+    match os {
+        OSLaunchTarget::MacOS => {
+            let codesign = |args: &[&str]| {
+                Command::new("codesign")
+                    .args(args)
+                    .arg(path)
+                    .output()
+                    .map_err(|e| VerifyError::Tool("codesign", e))
+            };
+
+            let verified = codesign(&["--verify", "--deep", "--strict"])?;
+            if !verified.status.success() {
+                return Err(VerifyError::Invalid(output_text(&verified)));
+            }
+            // `codesign -d` writes its details to stderr
+            let details = codesign(&["-dv", "--verbose=2"])?;
+            parse_codesign(&String::from_utf8_lossy(&details.stderr))
+                .ok_or_else(|| VerifyError::Invalid(output_text(&details)))
+        }
+        OSLaunchTarget::Windows { .. } => {
+            let script = format![
+                "$s = Get-AuthenticodeSignature -LiteralPath '{}'; $s.Status; $s.SignerCertificate.Subject",
+                path.to_string_lossy().replace('\'', "''")
+            ];
+            let output = Command::new("powershell")
+                .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+                .output()
+                .map_err(|e| VerifyError::Tool("powershell", e))?;
+
+            parse_authenticode(&String::from_utf8_lossy(&output.stdout))
+                .ok_or_else(|| VerifyError::Invalid(output_text(&output)))
+        }
+        OSLaunchTarget::Linux => Err(VerifyError::Unsupported(os.clone())),
+    }
+}
+
+fn output_text(output: &Output) -> String {
+    let mut text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        text = format!["{}\n{}", text, stderr.trim()];
+    }
+    text.trim().to_string()
+}
+
+/// Reads the `Authority=` and `TeamIdentifier=` lines of `codesign -dv --verbose=2`.
+fn parse_codesign(details: &str) -> Option<SignatureInfo> {
+    let authorities: Vec<String> = details
+        .lines()
+        .filter_map(|l| l.strip_prefix("Authority="))
+        .map(str::to_string)
+        .collect();
+    let team_id = details
+        .lines()
+        .find_map(|l| l.strip_prefix("TeamIdentifier="))
+        .filter(|id| *id != "not set")
+        .map(str::to_string);
+
+    // "Developer ID Application: Stichting Blender Foundation (68UA947AUU)"
+    let first = authorities.first()?;
+    let name = first.split_once(": ").map_or(first.as_str(), |(_, n)| n);
+    let signer = match name.rsplit_once(" (") {
+        Some((n, _)) => n,
+        None => name,
+    };
+
+    Some(SignatureInfo {
+        signer: signer.to_string(),
+        authorities,
+        team_id,
+    })
+}
+
+/// Reads the status and subject lines printed by the PowerShell script in [`verify_signature`].
+fn parse_authenticode(output: &str) -> Option<SignatureInfo> {
+    let mut lines = output.lines().map(str::trim).filter(|l| !l.is_empty());
+    if lines.next()? != "Valid" {
+        return None;
+    }
+    // "CN=Stichting Blender Foundation, O=Stichting Blender Foundation, L=Amsterdam, C=NL"
+    let subject = lines.next()?;
+    let signer = subject
+        .split(", ")
+        .find_map(|part| part.strip_prefix("CN="))
+        .unwrap_or(subject)
+        .trim_matches('"');
+
+    Some(SignatureInfo {
+        signer: signer.to_string(),
+        authorities: vec![subject.to_string()],
+        team_id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_authenticode, parse_codesign};
+
+    #[test]
+    fn test_parse_signatures() {
+        let codesign = "Executable=/Volumes/Blender/Blender.app/Contents/MacOS/Blender
+Identifier=org.blenderfoundation.blender
+Authority=Developer ID Application: Stichting Blender Foundation (68UA947AUU)
+Authority=Developer ID Certification Authority
+Authority=Apple Root CA
+TeamIdentifier=68UA947AUU
+";
+        let info = parse_codesign(codesign).unwrap();
+        assert![info.is_blender_foundation()];
+        assert_eq![info.authorities.len(), 3];
+        assert_eq![info.team_id.as_deref(), Some("68UA947AUU")];
+        assert![parse_codesign("Executable=/tmp/blender\nSignature=adhoc\n").is_none()];
+
+        let info = parse_authenticode(
+            "Valid\r\nCN=Stichting Blender Foundation, O=Stichting Blender Foundation, L=Amsterdam, C=NL\r\n",
+        )
+        .unwrap();
+        assert![info.is_blender_foundation()];
+        assert![parse_authenticode("NotSigned\r\n\r\n").is_none()];
+    }
+}
@@ -14,12 +14,30 @@
 //! * **Blender Build Management:** Easily download, manage, and organize Blender builds from different sources.
 //! * **Build Comparison and Selection:** Efficiently compare the characteristics of various builds to select the most suitable option for a specific project or purpose.
 //!
+//! WebAssembly
+//! ---
+//!
+//! The pure-data modules ([`info`] version types and parsing, [`search`], and the [`fetching`] schemas)
+//! compile for `wasm32-unknown-unknown`. Anything that touches processes, the user's project directories,
+//! or scans the filesystem ([`config`], [`repos`], [`extraction`], and launching builds) is only available on native targets.
+//! Build with `default-features = false` when targeting wasm, as `compressed-blends` depends on native `zstd`.
+//!
+//! Concurrency
+//! ---
+//!
+//! Several blrs processes (like a CLI and a GUI) may share the same library.
+//! Every file blrs writes (`.build_info` files and repo caches) is written to a temporary file
+//! and renamed into place, so readers only ever see a complete file. When two processes write
+//! the same file at once, the last one to finish wins.
+//!
 //! Selectable Features
 //! ---
 
 #![doc = document_features::document_features!()]
 
 /// BLRS level configuration settings.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
 pub mod config;
 /// Utilities and methods for downloading artifacts.
 pub mod fetching;
@@ -27,6 +45,8 @@ pub mod fetching;
 pub mod info;
 
 /// Collections to categorize build repositories.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
 pub mod repos;
 /// Methods for grouping and filtering builds.
 pub mod search;
@@ -34,7 +54,26 @@ pub mod search;
 /// Methods for filtering repos based on the build target.
 pub mod build_targets;
 
+/// Utilities for extracting downloaded build archives.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub mod extraction;
+
+/// Downloading and installing builds into the library.
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "reqwest", not(target_arch = "wasm32"))))
+)]
+pub mod install;
+
+mod atomic;
+mod error;
+
+#[cfg(not(target_arch = "wasm32"))]
 pub use config::{BLRSConfig, BLRSPaths};
+#[cfg(not(target_arch = "wasm32"))]
 pub use config::{DEFAULT_LIBRARY_FOLDER, DEFAULT_REPOS_FOLDER, PROJECT_DIRS};
+pub use error::BlrsError;
 pub use fetching::RemoteBuild;
 pub use info::{BasicBuildInfo, LocalBuild};
@@ -1,18 +1,33 @@
+mod index;
+mod prune;
+mod sync;
+
+pub use index::{full_index, FullIndex};
+pub use prune::{prune, PruneReport};
+pub use sync::{plan_sync, SyncPlan};
+
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     fmt::Debug,
     fmt::Display,
     fs::File,
     path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
+use chrono::{DateTime, Utc};
 use itertools::Itertools;
-use log::{debug, error};
+use log::{debug, error, warn};
 use serde::Serialize;
-use uuid::Uuid;
 
 use crate::{
+    build_targets::{
+        architectures_match, extensions_match, get_target_setup, is_build_extension,
+        platforms_match,
+    },
     fetching::{build_repository::BuildRepo, build_schemas::BlenderBuildSchema},
+    info::SYSTEM_REPO_ID,
+    search::{BInfoMatcher, VersionSearchQuery},
     BLRSPaths, BasicBuildInfo, LocalBuild, RemoteBuild,
 };
 
@@ -69,14 +84,30 @@ impl<B: Display + Debug> Variants<B> {
                 .v
                 .into_iter()
                 .filter(|build| {
-                    build.target_os == target.0
-                        && build.architecture == target.1
-                        && build.extension == target.2
+                    platforms_match(&build.target_os, target.0)
+                        && architectures_match(&build.architecture, target.1)
+                        && extensions_match(&build.extension, target.2)
                 })
                 .collect(),
             basic: self.basic,
         }
     }
+
+    /// Picks the variant to install for a target, preferring the extension given in `target`.
+    ///
+    /// If no variant for the platform and architecture has that extension, the first installable
+    /// one is picked instead. The preferred extension usually comes from [`crate::BLRSConfig::preferred_extension`].
+    pub fn best_for_target(&self, target: (&str, &str, &str)) -> Option<&BuildVariant<B>> {
+        let mut candidates = self.v.iter().filter(|build| {
+            platforms_match(&build.target_os, target.0)
+                && architectures_match(&build.architecture, target.1)
+        });
+
+        candidates
+            .clone()
+            .find(|build| extensions_match(&build.extension, target.2))
+            .or_else(|| candidates.find(|build| is_build_extension(&build.extension)))
+    }
 }
 
 /// An entry of a build.
@@ -87,8 +118,10 @@ pub enum BuildEntry {
     NotInstalled(Variants<RemoteBuild>),
 
     /// Indicates that a build for this variant is installed locally.
-    /// Provides details about the installed build.
-    Installed(String, LocalBuild),
+    /// Provides details about the installed build, and the newest update available for it.
+    ///
+    /// Updates are only looked for when reading repos with `find_updates`, see [`read_repos`].
+    Installed(String, LocalBuild, Option<Box<RemoteBuild>>),
 
     /// Represents an error encountered while processing or attempting to access a build.
     /// Includes the error information and possibly a path.
@@ -115,37 +148,333 @@ impl RepoEntry {
         match self {
             RepoEntry::Registered(_, vec) | RepoEntry::Unknown(_, vec) => vec
                 .iter()
-                .any(|entry| matches![entry, BuildEntry::Installed(_, _)]),
+                .any(|entry| matches![entry, BuildEntry::Installed(_, _, _)]),
             RepoEntry::Error(_, _) => false,
         }
     }
+
+    /// Collects the distinct branches of every installed and remote build in the repository.
+    pub fn branches(&self) -> BTreeSet<String> {
+        match self {
+            RepoEntry::Registered(_, vec) | RepoEntry::Unknown(_, vec) => vec
+                .iter()
+                .filter_map(|entry| match entry {
+                    BuildEntry::Installed(_, build, _) => Some(build.info.basic.ver.branch()),
+                    BuildEntry::NotInstalled(variants) => Some(variants.basic.ver.branch()),
+                    BuildEntry::Errored(_, _) => None,
+                })
+                .map(str::to_string)
+                .collect(),
+            RepoEntry::Error(_, _) => BTreeSet::new(),
+        }
+    }
+}
+
+/// A reference to an installed build, along with where it was found.
+#[derive(Debug, Clone, Copy)]
+pub struct BuildRef<'a> {
+    /// The ID of the repo the build is installed in.
+    pub repo_id: &'a str,
+    /// The name of the build's folder in the repo.
+    pub folder_name: &'a str,
+    /// The build itself.
+    pub build: &'a LocalBuild,
+}
+
+/// A single build from a list of [`RepoEntry`], along with the ID of the repo it is in. See [`flatten`].
+#[derive(Debug, Clone, Copy)]
+pub enum FlatBuild<'a> {
+    /// A build that is installed in the library.
+    Installed(BuildRef<'a>),
+    /// A build that is available to install from a repo.
+    Remote {
+        /// The ID of the repo the build is available from.
+        repo_id: &'a str,
+        /// The variants of the build.
+        variants: &'a Variants<RemoteBuild>,
+    },
+}
+
+impl<'a> FlatBuild<'a> {
+    /// The ID of the repo the build is in.
+    pub fn repo_id(&self) -> &'a str {
+        match self {
+            FlatBuild::Installed(b) => b.repo_id,
+            FlatBuild::Remote { repo_id, .. } => repo_id,
+        }
+    }
+
+    /// The basic info of the build.
+    pub fn basic(&self) -> &'a BasicBuildInfo {
+        match self {
+            FlatBuild::Installed(b) => &b.build.info.basic,
+            FlatBuild::Remote { variants, .. } => &variants.basic,
+        }
+    }
+
+    /// Whether the build is installed.
+    pub fn is_installed(&self) -> bool {
+        matches![self, FlatBuild::Installed(_)]
+    }
+}
+
+/// Iterates over every build in `repos`, installed or remote, in the order they are listed.
+///
+/// Errored repos and entries are skipped.
+pub fn flatten(repos: &[RepoEntry]) -> impl Iterator<Item = FlatBuild<'_>> {
+    repos
+        .iter()
+        .filter_map(|repo| match repo {
+            RepoEntry::Registered(r, vec) => Some((r.repo_id.as_str(), vec)),
+            RepoEntry::Unknown(name, vec) => Some((name.as_str(), vec)),
+            RepoEntry::Error(_, _) => None,
+        })
+        .flat_map(|(repo_id, entries)| {
+            entries.iter().filter_map(move |entry| match entry {
+                BuildEntry::Installed(folder_name, build, _) => {
+                    Some(FlatBuild::Installed(BuildRef {
+                        repo_id,
+                        folder_name,
+                        build,
+                    }))
+                }
+                BuildEntry::NotInstalled(variants) => Some(FlatBuild::Remote { repo_id, variants }),
+                BuildEntry::Errored(_, _) => None,
+            })
+        })
+}
+
+/// Iterates over every installed build, skipping errored repos and entries.
+pub(crate) fn installed_builds(repos: &[RepoEntry]) -> impl Iterator<Item = BuildRef<'_>> {
+    flatten(repos).filter_map(|b| match b {
+        FlatBuild::Installed(b) => Some(b),
+        FlatBuild::Remote { .. } => None,
+    })
+}
+
+/// Finds the newest installed build across every repo.
+///
+/// Builds are compared by their `commit_dt`, with ties broken by version.
+/// If `prefer_favorites` is set and any installed build is favorited, the newest favorite is returned instead.
+pub fn newest_installed(repos: &[RepoEntry], prefer_favorites: bool) -> Option<BuildRef<'_>> {
+    let newest = |favorites_only: bool| {
+        installed_builds(repos)
+            .filter(|b| !favorites_only || b.build.info.is_favorited)
+            .max_by(|a, b| a.build.info.basic.cmp(&b.build.info.basic))
+    };
+
+    prefer_favorites
+        .then(|| newest(true))
+        .flatten()
+        .or_else(|| newest(false))
+}
+
+/// Finds an installed build matched by `query`, ignoring remote builds.
+///
+/// Matching follows [`BInfoMatcher`], with each build's repo ID used as its repository nickname:
+/// - Exact and wildcard columns, like `daily/4.2.*`, are checked one build at a time, and the first
+///   installed build that matches is returned without looking at the rest.
+/// - Relative columns, like `4.^` or `4.-`, are resolved against every installed build, so `4.^`
+///   finds the newest installed 4.x build even if a newer one is available remotely.
+pub fn find_installed<'a>(
+    repos: &'a [RepoEntry],
+    query: &VersionSearchQuery,
+) -> Option<BuildRef<'a>> {
+    let builds: Vec<BuildRef> = installed_builds(repos).collect();
+    let infos: Vec<(&BasicBuildInfo, String)> = builds
+        .iter()
+        .map(|b| (&b.build.info.basic, b.repo_id.to_string()))
+        .collect();
+
+    BInfoMatcher::new(&infos)
+        .find_first(query)
+        .map(|i| builds[i])
+}
+
+/// Checks whether any installed build is matched by `query`. See [`find_installed`] for the matching rules.
+///
+/// This is the basis for installing a build only if it is missing.
+pub fn is_installed(repos: &[RepoEntry], query: &VersionSearchQuery) -> bool {
+    find_installed(repos, query).is_some()
+}
+
+/// Groups installed builds that share a commit hash, such as the same daily installed from two repos,
+/// and returns only the groups with more than one build.
+///
+/// Short and full hashes are reconciled with [`crate::info::hashes_match`]. Builds with an unknown
+/// hash are never considered duplicates. Groups keep the order builds are found in.
+pub fn find_duplicate_installs(repos: &[RepoEntry]) -> Vec<Vec<BuildRef<'_>>> {
+    let mut groups: Vec<Vec<BuildRef>> = vec![];
+    for build in installed_builds(repos).filter(|b| b.build.info.basic.ver.has_known_hash()) {
+        let hash = build.build.info.basic.ver.build_hash();
+        match groups
+            .iter_mut()
+            .find(|g| g[0].build.info.basic.ver.matches_hash(hash))
+        {
+            Some(group) => group.push(build),
+            None => groups.push(vec![build]),
+        }
+    }
+
+    groups.retain(|g| g.len() > 1);
+    groups
 }
 
+/// Finds every build made after `since`, newest first, such as the builds that are new since
+/// [`crate::config::History::last_time_checked`].
+///
+/// Builds are compared by their `commit_dt`. Remote builds are included unless `installed_only` is set.
+/// Builds are returned as [`FlatBuild`]s, as [`BuildRef`] only holds installed builds.
+pub fn builds_since(
+    repos: &[RepoEntry],
+    since: DateTime<Utc>,
+    installed_only: bool,
+) -> Vec<FlatBuild<'_>> {
+    flatten(repos)
+        .filter(|b| b.basic().commit_dt > since && (!installed_only || b.is_installed()))
+        .sorted_by(|a, b| b.basic().commit_dt.cmp(&a.basic().commit_dt))
+        .collect()
+}
+
+/// Finds every build, installed or remote, whose commit hash starts with `prefix`.
+///
+/// A short prefix can match several builds, so all of them are returned.
+/// Short and full hashes are reconciled with [`crate::info::hashes_match`], so `prefix`
+/// needs to be at least [`crate::info::MIN_HASH_PREFIX`] characters long unless it is a full hash.
+pub fn find_by_hash_prefix<'a>(repos: &'a [RepoEntry], prefix: &str) -> Vec<&'a BasicBuildInfo> {
+    flatten(repos)
+        .map(|b| b.basic())
+        .filter(|basic| basic.ver.matches_hash(prefix))
+        .collect()
+}
+
+/// Groups builds found by [`crate::info::detect_system_blenders`] into an unknown repo named
+/// [`crate::info::SYSTEM_REPO_ID`], so they can be listed alongside the library's repos.
+///
+/// Each build's folder path is used as its entry's folder name.
+pub fn system_repo_entry(builds: Vec<LocalBuild>) -> RepoEntry {
+    RepoEntry::Unknown(
+        SYSTEM_REPO_ID.to_string(),
+        builds
+            .into_iter()
+            .map(|b| BuildEntry::Installed(b.folder.to_string_lossy().to_string(), b, None))
+            .collect(),
+    )
+}
+
+/// Finds the newest available remote build in each repo, keyed by repo_id.
+///
+/// Only [`BuildEntry::NotInstalled`] variants are considered, and the build with the
+/// newest `commit_dt` wins. Repos with no remote builds are left out of the map.
+pub fn latest_per_repo(repos: &[RepoEntry]) -> HashMap<String, &RemoteBuild> {
+    repos
+        .iter()
+        .filter_map(|repo| {
+            let (id, entries) = match repo {
+                RepoEntry::Registered(r, vec) => (&r.repo_id, vec),
+                RepoEntry::Unknown(name, vec) => (name, vec),
+                RepoEntry::Error(_, _) => return None,
+            };
+
+            entries
+                .iter()
+                .filter_map(|entry| match entry {
+                    BuildEntry::NotInstalled(variants) => Some(variants.v.iter().map(|v| &v.b)),
+                    _ => None,
+                })
+                .flatten()
+                .max_by_key(|rb| rb.basic.commit_dt)
+                .map(|rb| (id.clone(), rb))
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "streaming-cache"))]
+fn parse_repo_cache(file: File) -> serde_json::Result<Vec<RemoteBuild>> {
+    serde_json::from_reader::<_, Vec<BlenderBuildSchema>>(std::io::BufReader::new(file))
+        .map(|schemas| schemas.into_iter().map(RemoteBuild::from).collect())
+}
+
+/// Parses the repo cache one schema at a time, converting each into a [`RemoteBuild`]
+/// before the next is parsed, so the full list of schemas is never held in memory.
+#[cfg(feature = "streaming-cache")]
+fn parse_repo_cache(file: File) -> serde_json::Result<Vec<RemoteBuild>> {
+    use serde::{de::SeqAccess, Deserializer};
+
+    struct RemoteBuildsVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for RemoteBuildsVisitor {
+        type Value = Vec<RemoteBuild>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a list of build schemas")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut builds = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(schema) = seq.next_element::<BlenderBuildSchema>()? {
+                builds.push(RemoteBuild::from(schema));
+            }
+            Ok(builds)
+        }
+    }
+
+    serde_json::Deserializer::from_reader(std::io::BufReader::new(file))
+        .deserialize_seq(RemoteBuildsVisitor)
+}
+
+/// Reads the builds stored in a repo cache file.
+///
+/// A missing cache holds no builds, so it returns an empty list.
+/// A cache that cannot be parsed returns an [`std::io::ErrorKind::InvalidData`] error.
+pub fn try_read_repo_cache(repo_cache_path: &Path) -> std::io::Result<Vec<RemoteBuild>> {
+    match File::open(repo_cache_path) {
+        Ok(file) => Ok(parse_repo_cache(file)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e),
+    }
+}
+
+/// Reads a repo cache, warning about and setting aside caches that cannot be parsed.
+///
+/// Corrupt caches are renamed to `<repo>.json.corrupt`, so the next fetch writes a fresh one.
 fn read_repo_cache(repo_cache_path: &Path) -> Vec<RemoteBuild> {
-    match repo_cache_path.exists() {
-        true => match File::open(repo_cache_path) {
-            Ok(file) => {
-                serde_json::from_reader::<_, Vec<BlenderBuildSchema>>(file).unwrap_or_default()
+    match try_read_repo_cache(repo_cache_path) {
+        Ok(builds) => builds,
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+            let corrupt = repo_cache_path.with_extension("json.corrupt");
+            warn![
+                "Repo cache {:?} is corrupt and will be moved to {:?}: {}",
+                repo_cache_path, corrupt, e
+            ];
+            if let Err(e) = std::fs::rename(repo_cache_path, &corrupt) {
+                error![
+                    "Failed to move {:?} to {:?}: {}",
+                    repo_cache_path, corrupt, e
+                ];
             }
-            Err(_) => vec![],
-        },
-        false => vec![],
+            vec![]
+        }
+        Err(e) => {
+            warn!["Failed to read repo cache {:?}: {}", repo_cache_path, e];
+            vec![]
+        }
     }
-    .into_iter()
-    .map(RemoteBuild::from)
-    .collect()
 }
 
 fn read_repo_cache_variants(repo_cache_path: &Path) -> HashMap<String, Variants<RemoteBuild>> {
     read_repo_cache(repo_cache_path)
         .into_iter()
-        .sorted_by_key(|k| k.basic.ver.clone())
-        .chunk_by(|k| k.basic.ver.clone())
+        .sorted_by_key(|k| k.basic.identity())
+        .chunk_by(|k| k.basic.identity())
         .into_iter()
-        .map(|(v, g)| {
-            (v.to_string(), {
+        .map(|(id, g)| {
+            (id, {
                 let variants: Vec<BuildVariant<RemoteBuild>> = g
-                    .filter(|b| !b.file_extension.as_ref().is_some_and(|e| e == "sha256"))
+                    .filter(|b| b.is_downloadable_build())
                     .map(|rb| BuildVariant {
                         target_os: rb.platform.clone().unwrap_or_default(),
                         architecture: rb.architecture.clone().unwrap_or_default(),
@@ -166,20 +495,36 @@ fn read_repo_cache_variants(repo_cache_path: &Path) -> HashMap<String, Variants<
         .collect()
 }
 
+/// Whether an error comes from a folder the user isn't allowed to read, which is skipped
+/// with a warning instead of failing or erroring the scan.
+fn is_denied(e: &std::io::Error, path: &Path) -> bool {
+    let denied = e.kind() == std::io::ErrorKind::PermissionDenied;
+    if denied {
+        warn!("Skipping {:?}, as it cannot be read: {}", path, e);
+    }
+    denied
+}
+
 fn read_local_entries(repo_library_path: &Path) -> Result<Vec<BuildEntry>, std::io::Error> {
     Ok(repo_library_path
         .read_dir()?
+        // Hidden entries are in-progress installs and downloads
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |f| !f.file_name().to_string_lossy().starts_with('.'))
+        })
         .filter_map(|item| match item {
             Ok(f) => match is_dir_or_link_to_dir(&f.path()) {
-                true => Some(
-                    match LocalBuild::read(&f.path().read_link().unwrap_or(f.path())) {
-                        Ok(build) => BuildEntry::Installed(
-                            f.file_name().to_str().unwrap().to_string(),
-                            build,
-                        ),
-                        Err(e) => BuildEntry::Errored(e, Some(f.path())),
-                    },
-                ),
+                // Links are resolved fully, as relative links are relative to the repo folder
+                true => match LocalBuild::read(&f.path().canonicalize().unwrap_or(f.path())) {
+                    Ok(build) => Some(BuildEntry::Installed(
+                        f.file_name().to_string_lossy().to_string(),
+                        build,
+                        None,
+                    )),
+                    Err(e) if is_denied(&e, &f.path()) => None,
+                    Err(e) => Some(BuildEntry::Errored(e, Some(f.path()))),
+                },
                 false => None,
             },
 
@@ -195,16 +540,37 @@ fn get_known_and_unknown_repos(
     let mut repo_map: HashMap<String, BuildRepo> =
         repos.into_iter().map(|r| (r.repo_id.clone(), r)).collect();
 
-    let folders: HashSet<String> = paths
-        .library
-        .read_dir()
-        .inspect_err(|e| error!("Failed to read {:?}: {}", paths.library, e))?
-        .filter_map(|item| {
-            let item = item.ok()?;
-            is_dir_or_link_to_dir(&item.path())
-                .then(|| item.file_name().to_str().unwrap().to_string())
-        })
-        .collect();
+    // A missing library is the same as an empty one, so that registered repos still show their remote builds
+    let folders: HashSet<String> = match paths.library.read_dir() {
+        Ok(dir) => dir
+            .filter_map(|item| {
+                let item = item.ok()?;
+                if !is_dir_or_link_to_dir(&item.path()) {
+                    return None;
+                }
+                match item.file_name().into_string() {
+                    Ok(name) => Some(name),
+                    Err(name) => {
+                        warn!(
+                            "Skipping repo folder {:?}, as its name is not valid UTF-8",
+                            name
+                        );
+                        None
+                    }
+                }
+            })
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            debug!("Library {:?} does not exist yet", paths.library);
+            HashSet::new()
+        }
+        // Registered repos can still show their remote builds, so a library that can't be read isn't fatal
+        Err(e) if is_denied(&e, &paths.library) => HashSet::new(),
+        Err(e) => {
+            error!("Failed to read {:?}: {}", paths.library, e);
+            return Err(e);
+        }
+    };
 
     let existing: Vec<Result<_, _>> = folders
         .into_iter()
@@ -227,55 +593,698 @@ fn get_known_and_unknown_repos(
 /// It handles both registered repositories (defined in the configuration) and
 /// unknown repositories present in the filesystem.
 ///
-/// The `installed_only` flag controls whether to only consider installed build entries
+/// The `installed_only` flag controls whether to only consider installed build entries.
+///
+/// With `find_updates`, each installed build is given the newest remote build of its repo that
+/// updates it, if any. An update is a newer build of the same branch and `major.minor` version,
+/// in the variant best suited to the current target. This reads every repo cache, so leave it off
+/// when only the installed builds are needed.
 pub fn read_repos(
     repos: Vec<BuildRepo>,
     paths: &BLRSPaths,
     installed_only: bool,
+    find_updates: bool,
 ) -> std::io::Result<Vec<RepoEntry>> {
-    let registered = get_known_and_unknown_repos(repos, paths)?;
+    read_repos_verbose(repos, paths, installed_only, find_updates).map(|(entries, _)| entries)
+}
 
-    Ok(registered
-        .into_iter()
-        .map(|r| {
-            debug!("Evaluating {:?}", r);
-            let id = match &r {
-                Ok(r) => r.repo_id.clone(),
-                Err(s) => s.clone(),
+/// Diagnostics about a library scan done by [`read_repos_verbose`].
+#[derive(Debug, Default, Clone)]
+pub struct ScanStats {
+    /// How many repos were read, including ones that errored.
+    pub repos_scanned: usize,
+    /// How many build folders were found in the library.
+    pub folders_scanned: usize,
+    /// How many folders had a readable `.build_info` file.
+    pub build_infos_read: usize,
+    /// How many folders had no `.build_info` file, and need it regenerated
+    /// (see [`LocalBuild::generate_from_exe`]) before they show up as installed.
+    pub build_infos_missing: usize,
+    /// How many remote builds were read from the repo caches.
+    pub remote_builds: usize,
+    /// Every path that failed to be read, along with why.
+    pub errored: Vec<(PathBuf, String)>,
+    /// How long the scan took.
+    pub elapsed: Duration,
+}
+
+impl ScanStats {
+    fn collect(entries: &[RepoEntry], paths: &BLRSPaths, elapsed: Duration) -> Self {
+        let mut stats = Self {
+            repos_scanned: entries.len(),
+            elapsed,
+            ..Default::default()
+        };
+
+        for repo in entries {
+            let (id, builds) = match repo {
+                RepoEntry::Registered(r, builds) => (r.repo_id.as_str(), builds),
+                RepoEntry::Unknown(name, builds) => (name.as_str(), builds),
+                RepoEntry::Error(name, e) => {
+                    stats
+                        .errored
+                        .push((paths.library.join(name), e.to_string()));
+                    continue;
+                }
             };
 
-            let library_path = paths.library.join(&id);
-            let entries = read_local_entries(&library_path);
-            let cache_path = paths.remote_repos.join(id.clone() + ".json");
-            let remote_variants = read_repo_cache_variants(&cache_path)
-                .into_iter()
-                .map(|(s, v)| (s, BuildEntry::NotInstalled(v)));
-
-            match (r, entries) {
-                (Ok(r), Ok(mut entries)) => {
-                    if !installed_only {
-                        entries = entries
-                            .into_iter()
-                            .map(|e| match &e {
-                                BuildEntry::Installed(_dir, local_build) => {
-                                    (local_build.info.basic.ver.to_string(), e)
-                                }
-                                BuildEntry::Errored(_, _) => (Uuid::new_v4().to_string(), e),
-                                BuildEntry::NotInstalled(_) => unreachable!(),
-                            })
-                            .chain(remote_variants)
-                            .unique_by(|(s, _)| s.clone())
-                            .map(|(_, e)| e)
-                            .collect();
+            for build in builds {
+                match build {
+                    BuildEntry::Installed(_, _, _) => {
+                        stats.folders_scanned += 1;
+                        stats.build_infos_read += 1;
+                    }
+                    BuildEntry::NotInstalled(variants) => stats.remote_builds += variants.v.len(),
+                    BuildEntry::Errored(e, path) => {
+                        stats.folders_scanned += path.is_some() as usize;
+                        if e.kind() == std::io::ErrorKind::NotFound {
+                            stats.build_infos_missing += 1;
+                        }
+                        let path = path.clone().unwrap_or_else(|| paths.library.join(id));
+                        stats.errored.push((path, e.to_string()));
                     }
-                    RepoEntry::Registered(r.clone().clone(), entries)
-                }
-                (Ok(r), Err(_)) => {
-                    RepoEntry::Registered(r, remote_variants.map(|(_, v)| v).collect())
                 }
-                (Err(name), Ok(entries)) => RepoEntry::Unknown(name, entries),
-                (Err(name), Err(err)) => RepoEntry::Error(name, err),
             }
-        })
-        .collect())
+        }
+
+        stats
+    }
+}
+
+/// Like [`read_repos`], but also returns [`ScanStats`] describing what was read and what went wrong.
+///
+/// Folders that are skipped because they can't be read (see [`read_repos_stream`]) are not counted.
+pub fn read_repos_verbose(
+    repos: Vec<BuildRepo>,
+    paths: &BLRSPaths,
+    installed_only: bool,
+    find_updates: bool,
+) -> std::io::Result<(Vec<RepoEntry>, ScanStats)> {
+    let start = Instant::now();
+    let entries: Vec<RepoEntry> =
+        read_repos_stream(repos, paths, installed_only, find_updates)?.collect();
+    let stats = ScanStats::collect(&entries, paths, start.elapsed());
+    debug!("Scanned library: {:?}", stats);
+
+    Ok((entries, stats))
+}
+
+/// Like [`read_repos`], but reads the library on tokio's blocking thread pool so that the
+/// directory walks and file reads don't stall the async runtime.
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub async fn read_repos_async(
+    repos: Vec<BuildRepo>,
+    paths: &BLRSPaths,
+    installed_only: bool,
+    find_updates: bool,
+) -> std::io::Result<Vec<RepoEntry>> {
+    let paths = paths.clone();
+    tokio::task::spawn_blocking(move || read_repos(repos, &paths, installed_only, find_updates))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+/// Reads build repositories one at a time, like [`read_repos`].
+///
+/// Each [`RepoEntry`] is only read once the iterator reaches it, so a frontend can show repos
+/// as they are processed instead of waiting for the whole library to be scanned.
+/// Only the library folder itself is read up front, and failing to read it is returned as an error.
+///
+/// Folders the user isn't allowed to read are skipped with a warning, so a partially restricted
+/// library still returns everything that is readable. If the library itself can't be read,
+/// only the registered repos' remote builds are returned.
+///
+/// The library may be a symlink, such as one pointing to an external drive. It is resolved
+/// once up front, so the folders of the builds that are read are inside its real location.
+pub fn read_repos_stream(
+    repos: Vec<BuildRepo>,
+    paths: &BLRSPaths,
+    installed_only: bool,
+    find_updates: bool,
+) -> std::io::Result<impl Iterator<Item = RepoEntry> + '_> {
+    let paths = BLRSPaths {
+        library: paths
+            .library
+            .canonicalize()
+            .unwrap_or(paths.library.clone()),
+        ..paths.clone()
+    };
+    let registered = get_known_and_unknown_repos(repos, &paths)?;
+
+    Ok(registered.into_iter().filter_map(move |r| {
+        let entry = read_repo_entry(r, &paths, installed_only, find_updates);
+        match &entry {
+            RepoEntry::Error(name, e) if is_denied(e, &paths.library.join(name)) => None,
+            _ => Some(entry),
+        }
+    }))
+}
+
+/// Finds the newest of `remote` that updates `build`, as described in [`read_repos`].
+fn find_update<'a>(
+    build: &LocalBuild,
+    remote: impl Iterator<Item = &'a Variants<RemoteBuild>>,
+    target: Option<(&str, &str, &str)>,
+) -> Option<RemoteBuild> {
+    let installed = &build.info.basic;
+    let same_series = |basic: &BasicBuildInfo| {
+        basic.ver.canonical_branch() == installed.ver.canonical_branch()
+            && basic.version().major == installed.version().major
+            && basic.version().minor == installed.version().minor
+    };
+
+    remote
+        .filter(|v| same_series(&v.basic) && v.basic > *installed)
+        .max_by(|a, b| a.basic.cmp(&b.basic))
+        .and_then(|v| target.and_then(|t| v.best_for_target(t)).or(v.v.first()))
+        .map(|variant| variant.b.clone())
+}
+
+/// Reads the installed and cached builds of a single repo.
+fn read_repo_entry(
+    r: Result<BuildRepo, String>,
+    paths: &BLRSPaths,
+    installed_only: bool,
+    find_updates: bool,
+) -> RepoEntry {
+    debug!("Evaluating {:?}", r);
+    let id = match &r {
+        Ok(r) => r.repo_id.clone(),
+        Err(s) => s.clone(),
+    };
+
+    let library_path = paths.library.join(&id);
+    let mut entries = read_local_entries(&library_path);
+    let cache_path = paths.remote_repos.join(id.clone() + ".json");
+    let remote_variants = read_repo_cache_variants(&cache_path);
+
+    if find_updates {
+        let target = get_target_setup();
+        for entry in entries.iter_mut().flatten() {
+            if let BuildEntry::Installed(_, build, update) = entry {
+                *update = find_update(build, remote_variants.values(), target).map(Box::new);
+            }
+        }
+    }
+
+    let remote_variants = remote_variants
+        .into_iter()
+        .map(|(s, v)| (s, BuildEntry::NotInstalled(v)));
+
+    match (r, entries) {
+        (Ok(r), Ok(mut entries)) => {
+            if !installed_only {
+                let mut seen = HashSet::new();
+                entries = entries
+                    .into_iter()
+                    .map(|e| match &e {
+                        BuildEntry::Installed(_dir, local_build, _) => {
+                            (Some(local_build.info.basic.identity()), e)
+                        }
+                        BuildEntry::Errored(_, _) => (None, e),
+                        BuildEntry::NotInstalled(_) => unreachable!(),
+                    })
+                    .chain(remote_variants.map(|(s, e)| (Some(s), e)))
+                    .filter(|(id, _)| id.as_ref().is_none_or(|id| seen.insert(id.clone())))
+                    .map(|(_, e)| e)
+                    .collect();
+            }
+            RepoEntry::Registered(r, entries)
+        }
+        (Ok(r), Err(_)) => RepoEntry::Registered(r, remote_variants.map(|(_, v)| v).collect()),
+        (Err(name), Ok(entries)) => RepoEntry::Unknown(name, entries),
+        (Err(name), Err(err)) => RepoEntry::Error(name, err),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{io, path::PathBuf};
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        fetching::build_repository::DEFAULT_REPOS,
+        info::{build_info::LocalBuildInfo, VerboseVersion},
+        search::{OrdPlacement, VersionSearchQuery},
+        BLRSPaths, BasicBuildInfo, LocalBuild, RemoteBuild,
+    };
+
+    use super::{
+        builds_since, find_by_hash_prefix, find_duplicate_installs, find_installed, find_update,
+        flatten, is_installed, newest_installed, read_repos, read_repos_verbose, BuildEntry,
+        BuildVariant, RepoEntry, Variants,
+    };
+
+    /// A fresh, unique set of paths under the temp dir, and the root to remove afterwards.
+    pub(crate) fn temp_paths() -> (PathBuf, BLRSPaths) {
+        let root = std::env::temp_dir().join(format!["blrs-test-{}", uuid::Uuid::new_v4()]);
+        let paths = BLRSPaths {
+            library: root.join("builds"),
+            remote_repos: root.join("remote-repos"),
+            staging: root.join("staging"),
+        };
+        (root, paths)
+    }
+
+    pub(crate) fn installed(name: &str, day: u32, minor: u64, favorite: bool) -> LocalBuild {
+        installed_with_hash(name, day, minor, favorite, None)
+    }
+
+    pub(crate) fn installed_with_hash(
+        name: &str,
+        day: u32,
+        minor: u64,
+        favorite: bool,
+        hash: Option<&str>,
+    ) -> LocalBuild {
+        LocalBuild {
+            folder: PathBuf::from(name),
+            info: LocalBuildInfo {
+                is_favorited: favorite,
+                ..LocalBuildInfo::new(BasicBuildInfo {
+                    ver: VerboseVersion::new(4, minor, 0, None, None, hash),
+                    commit_dt: Utc.with_ymd_and_hms(2024, 11, day, 0, 0, 0).unwrap(),
+                })
+            },
+        }
+    }
+
+    /// Wraps a build in an entry named after its folder.
+    pub(crate) fn entry(build: LocalBuild) -> BuildEntry {
+        let name = build.folder.to_string_lossy().into_owned();
+        BuildEntry::Installed(name, build, None)
+    }
+
+    #[test]
+    fn test_newest_installed() {
+        let repos = vec![
+            RepoEntry::Unknown(
+                "a".to_string(),
+                vec![
+                    entry(installed("old-favorite", 1, 2, true)),
+                    BuildEntry::Errored(io::Error::other("broken"), None),
+                    entry(installed("tied-lower", 20, 2, false)),
+                ],
+            ),
+            RepoEntry::Error("b".to_string(), io::Error::other("broken")),
+            RepoEntry::Unknown(
+                "c".to_string(),
+                vec![
+                    entry(installed("tied-higher", 20, 3, false)),
+                    entry(installed("older", 10, 4, false)),
+                ],
+            ),
+        ];
+
+        let newest = newest_installed(&repos, false).unwrap();
+        assert_eq![(newest.repo_id, newest.folder_name), ("c", "tied-higher")];
+
+        let favorite = newest_installed(&repos, true).unwrap();
+        assert_eq![favorite.folder_name, "old-favorite"];
+
+        assert![newest_installed(&repos[1..2], true).is_none()];
+    }
+
+    #[test]
+    fn test_find_by_hash_prefix() {
+        let repos = vec![RepoEntry::Unknown(
+            "repo".to_string(),
+            vec![
+                entry(installed_with_hash("a", 1, 2, false, Some("f723782e3a8c"))),
+                entry(installed_with_hash("b", 2, 3, false, Some("f723782ebbbb"))),
+                entry(installed_with_hash("c", 3, 4, false, Some("ddc9f92777cd"))),
+            ],
+        )];
+
+        assert_eq![find_by_hash_prefix(&repos, "f723782e").len(), 2];
+        assert_eq![find_by_hash_prefix(&repos, "f723782e3").len(), 1];
+        assert_eq![find_by_hash_prefix(&repos, "DDC9F92").len(), 1];
+        assert![find_by_hash_prefix(&repos, "f72").is_empty()];
+    }
+
+    #[test]
+    fn test_builds_since() {
+        let repos = vec![RepoEntry::Unknown(
+            "daily".to_string(),
+            vec![
+                entry(installed("a", 1, 2, false)),
+                entry(installed("b", 5, 3, false)),
+                BuildEntry::NotInstalled(Variants {
+                    v: vec![],
+                    basic: BasicBuildInfo {
+                        ver: VerboseVersion::new(4, 5, 0, None, None, None),
+                        commit_dt: Utc.with_ymd_and_hms(2024, 11, 9, 0, 0, 0).unwrap(),
+                    },
+                }),
+            ],
+        )];
+        let since = Utc.with_ymd_and_hms(2024, 11, 2, 0, 0, 0).unwrap();
+
+        let minors = |installed_only: bool| -> Vec<u64> {
+            builds_since(&repos, since, installed_only)
+                .iter()
+                .map(|b| b.basic().version().minor)
+                .collect()
+        };
+        assert_eq![minors(false), [5, 3]];
+        assert_eq![minors(true), [3]];
+    }
+
+    #[test]
+    fn test_find_duplicate_installs() {
+        let repos = vec![
+            RepoEntry::Unknown(
+                "daily".to_string(),
+                vec![
+                    entry(installed_with_hash("a", 1, 2, false, Some("f723782e3a8c"))),
+                    entry(installed_with_hash("b", 2, 3, false, Some("ddc9f92777cd"))),
+                    entry(installed("c", 3, 4, false)),
+                ],
+            ),
+            RepoEntry::Unknown(
+                "mirror".to_string(),
+                vec![
+                    entry(installed_with_hash("d", 1, 2, false, Some("f723782"))),
+                    entry(installed("e", 3, 4, false)),
+                ],
+            ),
+        ];
+
+        let groups: Vec<Vec<_>> = find_duplicate_installs(&repos)
+            .into_iter()
+            .map(|g| g.into_iter().map(|b| (b.repo_id, b.folder_name)).collect())
+            .collect();
+        assert_eq![groups, [[("daily", "a"), ("mirror", "d")]]];
+    }
+
+    #[test]
+    fn test_find_installed() {
+        let repos = vec![
+            RepoEntry::Unknown(
+                "daily".to_string(),
+                vec![
+                    entry(installed("a", 1, 2, false)),
+                    entry(installed("b", 2, 3, false)),
+                ],
+            ),
+            RepoEntry::Unknown(
+                "experimental".to_string(),
+                vec![
+                    entry(installed("c", 3, 1, false)),
+                    BuildEntry::NotInstalled(Variants {
+                        v: vec![],
+                        basic: BasicBuildInfo {
+                            ver: VerboseVersion::new(4, 5, 0, None, None, None),
+                            ..Default::default()
+                        },
+                    }),
+                ],
+            ),
+        ];
+        let query = |q: &str| VersionSearchQuery::try_from(q).unwrap();
+        let found = |q: &str| find_installed(&repos, &query(q)).map(|b| b.folder_name);
+
+        assert_eq![found("4.3"), Some("b")];
+        assert_eq![found("4.^"), Some("b")];
+        assert_eq![found("experimental/4.^"), Some("c")];
+        let oldest = VersionSearchQuery {
+            minor: OrdPlacement::Oldest,
+            ..Default::default()
+        };
+        assert_eq![
+            find_installed(&repos, &oldest).map(|b| b.folder_name),
+            Some("c")
+        ];
+        assert_eq![found("4.5"), None];
+        assert![is_installed(&repos, &query("Daily/4.2"))];
+        assert![!is_installed(&repos, &query("experimental/4.2"))];
+    }
+
+    #[test]
+    fn test_best_for_target() {
+        let variant = |os: &str, extension: &str| BuildVariant {
+            b: format!["{}.{}", os, extension],
+            target_os: os.to_string(),
+            architecture: "x86_64".to_string(),
+            extension: extension.to_string(),
+        };
+        let variants = Variants {
+            v: vec![
+                variant("windows", "zip"),
+                variant("linux", "tar.xz.sha256"),
+                variant("linux", "tar.xz"),
+                variant("linux", "AppImage"),
+            ],
+            basic: BasicBuildInfo::default(),
+        };
+
+        let best = |ext: &str| {
+            variants
+                .best_for_target(("linux", "x86_64", ext))
+                .map(|v| v.b.as_str())
+        };
+        assert_eq![best("AppImage"), Some("linux.AppImage")];
+        assert_eq![best("xz"), Some("linux.tar.xz")];
+        assert_eq![best("zip"), Some("linux.tar.xz")];
+        assert_eq![
+            variants
+                .best_for_target(("darwin", "x86_64", "dmg"))
+                .map(|v| &v.b),
+            None
+        ];
+    }
+
+    #[test]
+    fn test_branches() {
+        let repo = RepoEntry::Unknown(
+            "repo".to_string(),
+            vec![
+                entry(installed("a", 1, 2, false)),
+                entry(installed("b", 2, 3, false)),
+                BuildEntry::Errored(io::Error::other("broken"), None),
+            ],
+        );
+        assert_eq![repo.branches().into_iter().collect::<Vec<_>>(), ["null"]];
+        assert![
+            RepoEntry::Error("b".to_string(), io::Error::other("broken"))
+                .branches()
+                .is_empty()
+        ];
+    }
+
+    #[test]
+    fn test_find_update() {
+        let remote = |day: u32, minor: u64| {
+            let basic = BasicBuildInfo {
+                ver: VerboseVersion::new(4, minor, 0, None, None, None),
+                commit_dt: Utc.with_ymd_and_hms(2024, 11, day, 0, 0, 0).unwrap(),
+            };
+            Variants {
+                v: vec![BuildVariant {
+                    b: RemoteBuild {
+                        link: format!["https://example.com/{}-{}.tar.xz", minor, day],
+                        basic: basic.clone(),
+                        platform: None,
+                        architecture: None,
+                        file_extension: None,
+                        file_size: None,
+                        file_mtime: None,
+                        patch_name: None,
+                        raw_branch: None,
+                        raw: None,
+                    },
+                    target_os: "linux".to_string(),
+                    architecture: "x86_64".to_string(),
+                    extension: "tar.xz".to_string(),
+                }],
+                basic,
+            }
+        };
+        let build = installed("a", 10, 2, false);
+
+        let newer = [remote(5, 2), remote(20, 2), remote(15, 2), remote(25, 3)];
+        let update = find_update(&build, newer.iter(), None).unwrap();
+        assert_eq![update.link, "https://example.com/2-20.tar.xz"];
+
+        let older = [remote(5, 2), remote(25, 3)];
+        assert![find_update(&build, older.iter(), None).is_none()];
+    }
+
+    #[test]
+    fn test_flatten() {
+        let repos = vec![
+            RepoEntry::Unknown(
+                "a".to_string(),
+                vec![
+                    entry(installed("installed", 1, 2, false)),
+                    BuildEntry::Errored(io::Error::other("broken"), None),
+                    BuildEntry::NotInstalled(Variants {
+                        v: vec![],
+                        basic: BasicBuildInfo::default(),
+                    }),
+                ],
+            ),
+            RepoEntry::Error("b".to_string(), io::Error::other("broken")),
+            RepoEntry::Unknown(
+                "c".to_string(),
+                vec![entry(installed("other", 1, 3, false))],
+            ),
+        ];
+
+        let flat: Vec<_> = flatten(&repos)
+            .map(|b| (b.repo_id(), b.is_installed(), b.basic().version().minor))
+            .collect();
+        assert_eq![flat, [("a", true, 2), ("a", false, 0), ("c", true, 3)]];
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_names() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let (root, paths) = temp_paths();
+        let mut build = installed("a", 1, 2, false);
+        build.folder = paths
+            .library
+            .join("repo")
+            .join(OsStr::from_bytes(b"build-\xff"));
+        std::fs::create_dir_all(&build.folder).unwrap();
+        build.write().unwrap();
+        std::fs::create_dir_all(paths.library.join(OsStr::from_bytes(b"repo-\xff"))).unwrap();
+
+        let repos = read_repos(vec![], &paths, false, false).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq![repos.len(), 1];
+        assert![matches![
+            &repos[0],
+            RepoEntry::Unknown(_, entries)
+                if matches![&entries[..], [BuildEntry::Installed(name, _, _)] if name == "build-\u{FFFD}"]
+        ]];
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permission_denied() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let (root, paths) = temp_paths();
+        let mut build = installed("a", 1, 2, false);
+        build.folder = paths.library.join("repo/readable");
+        fs::create_dir_all(&build.folder).unwrap();
+        build.write().unwrap();
+
+        let locked = [
+            paths.library.join("repo/locked"),
+            paths.library.join("locked"),
+        ];
+        for dir in &locked {
+            fs::create_dir_all(dir).unwrap();
+            fs::set_permissions(dir, fs::Permissions::from_mode(0o000)).unwrap();
+        }
+
+        let repos = read_repos(vec![], &paths, false, false);
+        for dir in &locked {
+            fs::set_permissions(dir, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        fs::remove_dir_all(&root).unwrap();
+
+        // Permissions are not enforced for root, so this only checks that nothing is reported as denied there
+        let denied = |e: &io::Error| e.kind() == io::ErrorKind::PermissionDenied;
+        let repos = repos.unwrap();
+        assert![repos.iter().all(|r| match r {
+            RepoEntry::Error(_, e) => !denied(e),
+            RepoEntry::Registered(_, entries) | RepoEntry::Unknown(_, entries) => entries
+                .iter()
+                .all(|e| !matches![e, BuildEntry::Errored(e, _) if denied(e)]),
+        })];
+        assert![repos.iter().any(|r| matches![
+            r,
+            RepoEntry::Unknown(name, entries)
+                if name == "repo" && entries.iter().any(|e| matches![e, BuildEntry::Installed(n, _, _) if n == "readable"])
+        ])];
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_library() {
+        use std::{fs, os::unix::fs::symlink};
+
+        let (root, paths) = temp_paths();
+        let real = root.join("drive/builds");
+        let mut build = installed("a", 1, 2, false);
+        build.folder = real.join("repo/a");
+        fs::create_dir_all(&build.folder).unwrap();
+        build.write().unwrap();
+        // A build linked relative to its repo folder
+        symlink("a", real.join("repo/linked")).unwrap();
+        symlink(&real, &paths.library).unwrap();
+
+        let repos = read_repos(vec![], &paths, false, false).unwrap();
+        let real = real.canonicalize().unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let [RepoEntry::Unknown(name, entries)] = &repos[..] else {
+            panic!["Expected a single unknown repo, got {:?}", repos]
+        };
+        assert_eq![name, "repo"];
+        assert_eq![entries.len(), 2];
+        assert![entries.iter().all(
+            |e| matches![e, BuildEntry::Installed(_, b, _) if b.folder == real.join("repo/a")]
+        )];
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_read_repos_async() {
+        let (_, paths) = temp_paths();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let repos = runtime
+            .block_on(super::read_repos_async(
+                DEFAULT_REPOS.to_vec(),
+                &paths,
+                false,
+                false,
+            ))
+            .unwrap();
+        assert_eq![repos.len(), DEFAULT_REPOS.len()];
+    }
+
+    #[test]
+    fn test_read_repos_verbose() {
+        let (root, paths) = temp_paths();
+        let mut build = installed("a", 1, 2, false);
+        build.folder = paths.library.join("repo/a");
+        std::fs::create_dir_all(&build.folder).unwrap();
+        build.write().unwrap();
+        let missing = paths.library.join("repo/no-info");
+        std::fs::create_dir_all(&missing).unwrap();
+
+        let (repos, stats) = read_repos_verbose(vec![], &paths, false, false).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq![repos.len(), 1];
+        assert_eq![stats.repos_scanned, 1];
+        assert_eq![stats.folders_scanned, 2];
+        assert_eq![stats.build_infos_read, 1];
+        assert_eq![stats.build_infos_missing, 1];
+        assert_eq![stats.errored.len(), 1];
+        assert_eq![stats.errored[0].0, missing];
+    }
+
+    #[test]
+    fn test_missing_library() {
+        let (_, paths) = temp_paths();
+
+        let repos = read_repos(DEFAULT_REPOS.to_vec(), &paths, false, false).unwrap();
+        assert_eq![repos.len(), DEFAULT_REPOS.len()];
+        assert![repos
+            .iter()
+            .all(|r| matches![r, RepoEntry::Registered(_, entries) if entries.is_empty()])];
+    }
 }
@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+
+use crate::{
+    build_targets::{filter_repos_by_target, get_target_setup},
+    info::{detect_system_blenders, SYSTEM_REPO_ID},
+    BLRSConfig, BasicBuildInfo, LocalBuild, RemoteBuild,
+};
+
+use super::{
+    installed_builds, read_repos, system_repo_entry, BuildEntry, BuildRef, RepoEntry, Variants,
+};
+
+/// Every build blrs knows about, merged into a single list of repos. See [`full_index`].
+#[derive(Debug)]
+pub struct FullIndex {
+    /// The repos of the index, in the order described by [`full_index`].
+    pub repos: Vec<RepoEntry>,
+}
+
+impl FullIndex {
+    /// Iterates over every installed build, including system builds.
+    pub fn installed(&self) -> impl Iterator<Item = BuildRef<'_>> {
+        installed_builds(&self.repos)
+    }
+
+    /// Iterates over every build that is available to install, along with the ID of its repo.
+    pub fn available(&self) -> impl Iterator<Item = (&str, &Variants<RemoteBuild>)> {
+        self.repos
+            .iter()
+            .filter_map(|repo| match repo {
+                RepoEntry::Registered(r, vec) => Some((r.repo_id.as_str(), vec)),
+                _ => None,
+            })
+            .flat_map(|(repo_id, entries)| {
+                entries.iter().filter_map(move |entry| match entry {
+                    BuildEntry::NotInstalled(variants) => Some((repo_id, variants)),
+                    _ => None,
+                })
+            })
+    }
+}
+
+/// Builds a [`FullIndex`] of the library's installed builds, the cached remote builds of the
+/// configured repos, and optionally the Blender installs found by [`detect_system_blenders`].
+///
+/// Only the repo caches are read, so they should be fetched beforehand to get up-to-date remote builds.
+/// Remote builds are filtered down to the current target (see [`filter_repos_by_target`]), and
+/// registered repos left without any builds are dropped.
+///
+/// When the same build shows up more than once, the first source in this list wins:
+/// 1. Builds installed in the library.
+/// 2. Remote builds from the repo caches.
+/// 3. System builds.
+///
+/// Registered repos come first, in the order of [`BLRSConfig::repos`], followed by unknown repos
+/// sorted by name, and finally the system builds under [`crate::info::SYSTEM_REPO_ID`].
+/// Repos that could not be read are kept at the end, so they can still be reported.
+/// Within each repo, builds are sorted from newest to oldest.
+pub fn full_index(config: &BLRSConfig, include_system: bool) -> std::io::Result<FullIndex> {
+    let mut repos = read_repos(config.repos.clone(), &config.paths, false, false)?;
+    if get_target_setup().is_some() {
+        repos = filter_repos_by_target(repos, None);
+    }
+    let system = match include_system {
+        true => detect_system_blenders(),
+        false => vec![],
+    };
+
+    Ok(FullIndex {
+        repos: merge_index(repos, system, config),
+    })
+}
+
+/// Orders the repos and appends the system builds that aren't already part of them.
+fn merge_index(
+    mut repos: Vec<RepoEntry>,
+    system: Vec<LocalBuild>,
+    config: &BLRSConfig,
+) -> Vec<RepoEntry> {
+    let known: HashSet<String> = repos
+        .iter()
+        .flat_map(|repo| match repo {
+            RepoEntry::Registered(_, vec) | RepoEntry::Unknown(_, vec) => vec.as_slice(),
+            RepoEntry::Error(_, _) => &[],
+        })
+        .filter_map(|entry| match entry {
+            BuildEntry::Installed(_, build, _) => Some(build.info.basic.identity()),
+            BuildEntry::NotInstalled(variants) => Some(variants.basic.identity()),
+            BuildEntry::Errored(_, _) => None,
+        })
+        .collect();
+
+    let system: Vec<_> = system
+        .into_iter()
+        .filter(|b| !known.contains(&b.info.basic.identity()))
+        .collect();
+    if !system.is_empty() {
+        repos.push(system_repo_entry(system));
+    }
+
+    for repo in repos.iter_mut() {
+        if let RepoEntry::Registered(_, vec) | RepoEntry::Unknown(_, vec) = repo {
+            vec.sort_by(|a, b| entry_basic(b).cmp(&entry_basic(a)));
+        }
+    }
+
+    let rank = |repo: &RepoEntry| match repo {
+        RepoEntry::Registered(r, _) => (
+            0,
+            config
+                .repos
+                .iter()
+                .position(|c| c.repo_id == r.repo_id)
+                .unwrap_or(usize::MAX),
+            String::new(),
+        ),
+        RepoEntry::Unknown(name, _) if name == SYSTEM_REPO_ID => (2, 0, String::new()),
+        RepoEntry::Unknown(name, _) => (1, 0, name.clone()),
+        RepoEntry::Error(name, _) => (3, 0, name.clone()),
+    };
+    repos.sort_by_cached_key(rank);
+
+    repos
+}
+
+/// The build info used to sort entries, with errored entries sorted last.
+fn entry_basic(entry: &BuildEntry) -> Option<&BasicBuildInfo> {
+    match entry {
+        BuildEntry::Installed(_, build, _) => Some(&build.info.basic),
+        BuildEntry::NotInstalled(variants) => Some(&variants.basic),
+        BuildEntry::Errored(_, _) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        info::{build_info::LocalBuildInfo, VerboseVersion, SYSTEM_REPO_ID},
+        repos::{BuildEntry, RepoEntry},
+        BLRSConfig, BasicBuildInfo, LocalBuild,
+    };
+
+    use super::merge_index;
+
+    fn build(minor: u64) -> LocalBuild {
+        LocalBuild {
+            folder: PathBuf::from(format!["4.{}", minor]),
+            info: LocalBuildInfo::new(BasicBuildInfo {
+                ver: VerboseVersion::new(4, minor, 0, None, None, None),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_merge_index() {
+        let config = BLRSConfig::default();
+        let first = config.repos[0].clone();
+        let repos = vec![
+            RepoEntry::Unknown("b".to_string(), vec![]),
+            RepoEntry::Unknown(
+                "a".to_string(),
+                vec![
+                    BuildEntry::Installed("4.1".to_string(), build(1), None),
+                    BuildEntry::Installed("4.2".to_string(), build(2), None),
+                ],
+            ),
+            RepoEntry::Registered(first.clone(), vec![]),
+        ];
+
+        let merged = merge_index(repos, vec![build(2), build(3)], &config);
+        let names: Vec<_> = merged
+            .iter()
+            .map(|r| match r {
+                RepoEntry::Registered(r, _) => r.repo_id.clone(),
+                RepoEntry::Unknown(name, _) | RepoEntry::Error(name, _) => name.clone(),
+            })
+            .collect();
+        assert_eq![names, [first.repo_id.as_str(), "a", "b", SYSTEM_REPO_ID]];
+
+        // Newest first, and the system 4.2 is already installed in "a"
+        let RepoEntry::Unknown(_, a) = &merged[1] else {
+            panic!()
+        };
+        assert![matches![&a[0], BuildEntry::Installed(name, _, _) if name == "4.2"]];
+        let RepoEntry::Unknown(_, system) = &merged[3] else {
+            panic!()
+        };
+        assert_eq![system.len(), 1];
+    }
+}
@@ -0,0 +1,114 @@
+use std::{fs, io, path::PathBuf};
+
+use log::{debug, info};
+
+use crate::info::SYSTEM_REPO_ID;
+
+use super::BuildRef;
+
+/// What [`prune`] did with each build it was given.
+#[derive(Debug, Default)]
+pub struct PruneReport {
+    /// The folders of the builds that were deleted.
+    pub removed: Vec<PathBuf>,
+    /// The folders of the builds that were kept because they are locked.
+    pub kept_locked: Vec<PathBuf>,
+}
+
+/// Deletes the folders of `builds` from the library.
+///
+/// Builds that are locked (see [`crate::info::build_info::LocalBuildInfo::is_locked`]) are kept and listed in
+/// [`PruneReport::kept_locked`], unless `force` is set. Builds in the [`SYSTEM_REPO_ID`] repo are never
+/// deleted, since they are not managed by blrs.
+///
+/// Deletion stops at the first build that fails to be removed.
+pub fn prune<'a>(
+    builds: impl IntoIterator<Item = BuildRef<'a>>,
+    force: bool,
+) -> io::Result<PruneReport> {
+    let mut report = PruneReport::default();
+
+    for b in builds {
+        let folder = &b.build.folder;
+        if b.repo_id == SYSTEM_REPO_ID {
+            debug!["Not pruning system build {:?}", folder];
+            continue;
+        }
+        if b.build.info.is_locked && !force {
+            debug!["Not pruning locked build {:?}", folder];
+            report.kept_locked.push(folder.clone());
+            continue;
+        }
+
+        info!["Pruning build {:?}", folder];
+        fs::remove_dir_all(folder)?;
+        report.removed.push(folder.clone());
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        info::{build_info::LocalBuildInfo, SYSTEM_REPO_ID},
+        repos::BuildRef,
+        LocalBuild,
+    };
+
+    use super::prune;
+
+    fn refs<'a>(builds: &[(&'a str, &'a LocalBuild)]) -> Vec<BuildRef<'a>> {
+        builds
+            .iter()
+            .map(|&(repo_id, build)| BuildRef {
+                repo_id,
+                folder_name: "",
+                build,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_prune_locked() {
+        let root = std::env::temp_dir().join(format!["blrs-test-{}", uuid::Uuid::new_v4()]);
+
+        let build = |name: &str, locked: bool| {
+            let folder = root.join(name);
+            fs::create_dir_all(&folder).unwrap();
+            let mut b = LocalBuild {
+                folder,
+                info: LocalBuildInfo::new(Default::default()),
+            };
+            b.set_locked(locked).unwrap();
+            b
+        };
+        let (free, locked, system) = (
+            build("free", false),
+            build("locked", true),
+            build("system", false),
+        );
+        assert![LocalBuild::read(&locked.folder).unwrap().info.is_locked];
+
+        let report = prune(
+            refs(&[
+                ("repo", &free),
+                ("repo", &locked),
+                (SYSTEM_REPO_ID, &system),
+            ]),
+            false,
+        )
+        .unwrap();
+        assert_eq![report.removed, vec![free.folder.clone()]];
+        assert_eq![report.kept_locked, vec![locked.folder.clone()]];
+        assert![locked.folder.exists() && system.folder.exists()];
+
+        let report = prune(refs(&[("repo", &locked)]), true).unwrap();
+        assert_eq![report.removed, vec![locked.folder.clone()]];
+        assert![!locked.folder.exists()];
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+
+use crate::{
+    build_targets::get_target_setup,
+    fetching::build_repository::BuildRepo,
+    search::{BInfoMatcher, VersionSearchQuery},
+    BasicBuildInfo, RemoteBuild,
+};
+
+use super::{BuildEntry, BuildRef, RepoEntry};
+
+/// The changes needed to have every build of a set of queries installed. See [`plan_sync`].
+#[derive(Debug, Default)]
+pub struct SyncPlan<'a> {
+    /// Builds that need to be installed, along with the repo they come from.
+    /// These can be passed straight to `install::install_many`.
+    pub to_install: Vec<(&'a BuildRepo, &'a RemoteBuild)>,
+    /// Installed builds that already satisfy a query.
+    pub already_present: Vec<BuildRef<'a>>,
+    /// Queries that did not match any installed or available build.
+    pub unresolved: Vec<VersionSearchQuery>,
+}
+
+/// A build found in the repos, and where it came from.
+enum Candidate<'a> {
+    Installed(BuildRef<'a>),
+    Remote(&'a BuildRepo, &'a RemoteBuild),
+}
+
+/// Works out what needs to be installed so that every query in `desired` has a matching build.
+///
+/// Each query is matched against the installed and available builds together, so `4.2.^` picks the
+/// newest 4.2 build whether or not it is installed. Matches that are installed go to
+/// [`SyncPlan::already_present`], and the rest go to [`SyncPlan::to_install`].
+/// A build matched by several queries is only listed once.
+///
+/// The variant to install is picked with [`super::Variants::best_for_target`] for the current target,
+/// so `repos` should already be filtered down to it (see [`crate::build_targets::filter_repos_by_target`]).
+pub fn plan_sync<'a>(desired: &[VersionSearchQuery], repos: &'a [RepoEntry]) -> SyncPlan<'a> {
+    let target = get_target_setup();
+
+    let candidates: Vec<(Candidate, &BasicBuildInfo, String)> = repos
+        .iter()
+        .flat_map(|repo| {
+            let (id, registered, entries) = match repo {
+                RepoEntry::Registered(r, vec) => (r.repo_id.as_str(), Some(r), vec.as_slice()),
+                RepoEntry::Unknown(name, vec) => (name.as_str(), None, vec.as_slice()),
+                RepoEntry::Error(_, _) => ("", None, [].as_slice()),
+            };
+
+            entries.iter().filter_map(move |entry| match entry {
+                BuildEntry::Installed(folder_name, build, _) => Some((
+                    Candidate::Installed(BuildRef {
+                        repo_id: id,
+                        folder_name,
+                        build,
+                    }),
+                    &build.info.basic,
+                    id.to_string(),
+                )),
+                BuildEntry::NotInstalled(variants) => {
+                    let variant = target
+                        .and_then(|t| variants.best_for_target(t))
+                        .or(variants.v.first())?;
+                    Some((
+                        Candidate::Remote(registered?, &variant.b),
+                        &variants.basic,
+                        id.to_string(),
+                    ))
+                }
+                BuildEntry::Errored(_, _) => None,
+            })
+        })
+        .collect();
+
+    let infos: Vec<(&BasicBuildInfo, String)> = candidates
+        .iter()
+        .map(|(_, basic, id)| (*basic, id.clone()))
+        .collect();
+    let matcher = BInfoMatcher::new(&infos);
+
+    let mut plan = SyncPlan::default();
+    let mut seen = HashSet::new();
+    for query in desired {
+        let found = matcher.find_indices(query);
+        if found.is_empty() {
+            plan.unresolved.push(query.clone());
+        }
+
+        for i in found.into_iter().filter(|&i| seen.insert(i)) {
+            match candidates[i].0 {
+                Candidate::Installed(build) => plan.already_present.push(build),
+                Candidate::Remote(repo, build) => plan.to_install.push((repo, build)),
+            }
+        }
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        fetching::build_repository::DEFAULT_REPOS,
+        info::{build_info::LocalBuildInfo, VerboseVersion},
+        repos::{BuildEntry, BuildVariant, RepoEntry, Variants},
+        search::VersionSearchQuery,
+        BasicBuildInfo, LocalBuild, RemoteBuild,
+    };
+
+    use super::plan_sync;
+
+    fn basic(minor: u64) -> BasicBuildInfo {
+        BasicBuildInfo {
+            ver: VerboseVersion::new(4, minor, 0, None, Some("stable"), None),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_sync() {
+        let installed = LocalBuild {
+            folder: PathBuf::from("4.1"),
+            info: LocalBuildInfo::new(basic(1)),
+        };
+        let remote = RemoteBuild {
+            link: "https://example.com/blender-4.2.0-linux.x86_64.tar.xz".to_string(),
+            basic: basic(2),
+            platform: Some("linux".to_string()),
+            architecture: Some("x86_64".to_string()),
+            file_extension: Some("tar.xz".to_string()),
+            file_size: None,
+            file_mtime: None,
+            patch_name: None,
+            raw_branch: None,
+            raw: None,
+        };
+        let repos = vec![RepoEntry::Registered(
+            DEFAULT_REPOS[0].clone(),
+            vec![
+                BuildEntry::Installed("4.1".to_string(), installed, None),
+                BuildEntry::NotInstalled(Variants {
+                    v: vec![BuildVariant {
+                        target_os: "linux".to_string(),
+                        architecture: "x86_64".to_string(),
+                        extension: "tar.xz".to_string(),
+                        b: remote,
+                    }],
+                    basic: basic(2),
+                }),
+            ],
+        )];
+
+        let query = |q: &str| VersionSearchQuery::try_from(q).unwrap();
+        let plan = plan_sync(
+            &[
+                query("4.1.0"),
+                query("4.^.0"),
+                query("4.2.0"),
+                query("3.6.0"),
+            ],
+            &repos,
+        );
+
+        assert_eq![plan.already_present.len(), 1];
+        assert_eq![plan.already_present[0].folder_name, "4.1"];
+        assert_eq![plan.to_install.len(), 1];
+        assert_eq![plan.to_install[0].1.basic.version().minor, 2];
+        assert_eq![plan.unresolved.len(), 1];
+        assert_eq![plan.unresolved[0].to_string(), query("3.6.0").to_string()];
+    }
+}
@@ -15,6 +15,31 @@ pub enum WildPlacement<T: PartialEq> {
     Any,
     /// Find a specific value in a group.
     Exact(T),
+    /// Find every value in a group except a specific one.
+    Not(T),
+}
+
+impl<T: PartialEq> WildPlacement<T> {
+    /// Checks a value against the placement, where `eq` decides whether a value matches the placement's `T`.
+    ///
+    /// ```
+    /// use blrs::search::WildPlacement;
+    ///
+    /// let eq = |t: &String| t == "stable";
+    /// assert![WildPlacement::Any.matches_by(eq)];
+    /// assert![WildPlacement::Exact("stable".to_string()).matches_by(eq)];
+    /// assert![!WildPlacement::Not("stable".to_string()).matches_by(eq)];
+    /// ```
+    pub fn matches_by<F>(&self, eq: F) -> bool
+    where
+        F: FnOnce(&T) -> bool,
+    {
+        match self {
+            WildPlacement::Any => true,
+            WildPlacement::Exact(t) => eq(t),
+            WildPlacement::Not(t) => !eq(t),
+        }
+    }
 }
 
 impl<T: Display + PartialEq> Display for WildPlacement<T> {
@@ -22,17 +47,22 @@ impl<T: Display + PartialEq> Display for WildPlacement<T> {
         f.write_str(&match self {
             WildPlacement::Any => "*".to_string(),
             WildPlacement::Exact(t) => format!["{t}"],
+            WildPlacement::Not(t) => format!["!{t}"],
         })
     }
 }
 
 impl<T: FromStr + PartialEq> From<&str> for WildPlacement<T> {
+    /// Parses a placement, where a leading `!` negates the value.
+    ///
+    /// `!*` would match nothing, so it is treated like `*`. [`VersionSearchQuery::try_from`] rejects it instead.
     fn from(value: &str) -> Self {
+        let parse = |s: &str| s.parse::<T>().ok();
         match value.trim() {
-            "*" => WildPlacement::Any,
-            s => match s.parse::<T>() {
-                Ok(t) => WildPlacement::Exact(t),
-                Err(_) => WildPlacement::Any,
+            "*" | "!*" => WildPlacement::Any,
+            s => match s.strip_prefix('!') {
+                Some(s) => parse(s).map_or(WildPlacement::Any, WildPlacement::Not),
+                None => parse(s).map_or(WildPlacement::Any, WildPlacement::Exact),
             },
         }
     }
@@ -107,6 +137,58 @@ impl<T: Ord + PartialOrd + PartialEq + Debug> OrdPlacement<T> {
     }
 }
 
+impl<T: Ord + PartialOrd + PartialEq> OrdPlacement<T> {
+    /// Whether the placement depends on the other values in the group, like [`OrdPlacement::Latest`]
+    /// and [`OrdPlacement::Oldest`] do.
+    pub fn is_relative(&self) -> bool {
+        matches![self, OrdPlacement::Latest | OrdPlacement::Oldest]
+    }
+
+    /// Checks a single value against the placement, ignoring the rest of the group.
+    ///
+    /// Relative placements like [`OrdPlacement::Latest`] accept every value, as they can only be
+    /// resolved against the whole group. See [`OrdPlacement::is_relative`].
+    pub fn matches_alone(&self, value: &T) -> bool {
+        match self {
+            OrdPlacement::Exact(t) => t == value,
+            _ => true,
+        }
+    }
+
+    /// Retains only the indices whose values pass the placement check, without allocating.
+    ///
+    /// The `key` function must take an index and return the value that is compared.
+    ///
+    /// ```
+    /// use blrs::search::OrdPlacement;
+    ///
+    /// let v = vec![0, 65, 4, 65, 10];
+    /// let mut indices: Vec<usize> = (0..v.len()).collect();
+    /// OrdPlacement::Latest.retain_indices(&mut indices, |i| &v[i]);
+    /// assert_eq![indices, vec![1, 3]];
+    /// ```
+    pub fn retain_indices<'a, F>(&self, indices: &mut Vec<usize>, key: F)
+    where
+        F: Fn(usize) -> &'a T,
+        T: 'a,
+    {
+        match self {
+            OrdPlacement::Any => {}
+            OrdPlacement::Latest => {
+                if let Some(latest) = indices.iter().map(|&i| key(i)).max() {
+                    indices.retain(|&i| key(i) == latest);
+                }
+            }
+            OrdPlacement::Oldest => {
+                if let Some(oldest) = indices.iter().map(|&i| key(i)).min() {
+                    indices.retain(|&i| key(i) == oldest);
+                }
+            }
+            OrdPlacement::Exact(t) => indices.retain(|&i| key(i) == t),
+        }
+    }
+}
+
 impl<T: Display + PartialOrd + PartialEq> Display for OrdPlacement<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&match self {
@@ -149,6 +231,7 @@ impl<T: FromStr + PartialOrd + PartialEq> From<&str> for OrdPlacement<T> {
 /// - `*`    | Match any item in that column
 /// - `-`    | Match the smallest/oldest item in that column
 /// - `<n>`  | Match a specific item in that column
+/// - `!<n>` | Match everything but a specific item (repository and branch only)
 ///
 /// Valid examples of version search queries are:
 ///```md
@@ -165,6 +248,8 @@ impl<T: FromStr + PartialOrd + PartialEq> From<&str> for OrdPlacement<T> {
 /// And of course, a full example:
 ///
 /// 4.3.^-stable+cb886aba06d5@2024-07-31T23:53:51+00:00
+///
+/// !daily/4.^.^-!experimental
 ///```
 ///
 pub const VERSION_SEARCH_SYNTAX: &str =
@@ -183,7 +268,6 @@ pub const VERSION_SEARCH_SYNTAX: &str =
 /// `(?:\@([\dT\+\:Z\ \^\*\-]+))?`  -- commit time (saved as ^|*|- or an isoformat) (optional)
 ///
 /// `$`                             -- end of string
-
 pub static VERSION_SEARCH_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     RegexBuilder::new(
         r"^
@@ -245,6 +329,17 @@ impl VersionSearchQuery {
             ..self
         }
     }
+
+    /// Whether any of the query's ordered columns are relative (see [`OrdPlacement::is_relative`]).
+    ///
+    /// Relative queries like `4.^` can only be resolved against a whole list of builds, while the
+    /// rest can be checked one build at a time.
+    pub fn is_relative(&self) -> bool {
+        self.major.is_relative()
+            || self.minor.is_relative()
+            || self.patch.is_relative()
+            || self.commit_dt.is_relative()
+    }
 }
 
 impl Display for VersionSearchQuery {
@@ -274,12 +369,17 @@ impl Display for VersionSearchQuery {
 /// use blrs::search::FromError;
 /// assert![matches![VersionSearchQuery::try_from("*.*.*"), Ok(_)]];
 /// assert![matches![VersionSearchQuery::try_from("incorrect!"), Err(FromError::CannotCaptureViaRegex)]];
+/// assert![matches![VersionSearchQuery::try_from("4.3-!*"), Err(FromError::NegatedWildcard)]];
 /// ```
 pub enum FromError {
     /// This can occur when the string could not be parsed by the [VERSION_SEARCH_REGEX].
 
     #[error("Could not get required parameters from the given string")]
     CannotCaptureViaRegex,
+
+    /// This occurs when a column is given `!*`, which would not match anything.
+    #[error("`!*` would never match anything")]
+    NegatedWildcard,
 }
 
 impl TryFrom<&str> for VersionSearchQuery {
@@ -289,10 +389,18 @@ impl TryFrom<&str> for VersionSearchQuery {
         let captures = VERSION_SEARCH_REGEX
             .captures(value)
             .ok_or(Self::Error::CannotCaptureViaRegex)?;
+        if [1, 5]
+            .into_iter()
+            .filter_map(|i| captures.get(i))
+            .any(|m| m.as_str().trim() == "!*")
+        {
+            return Err(FromError::NegatedWildcard);
+        }
 
+        // Nicknames are matched regardless of case, so they are normalized to lowercase
         let repository = captures
             .get(1)
-            .map(|m| WildPlacement::from(m.as_str()))
+            .map(|m| WildPlacement::from(m.as_str().trim().to_lowercase().as_str()))
             .unwrap_or_default();
 
         let (major, minor, patch) = match (captures.get(2), captures.get(3), captures.get(4)) {
@@ -1,8 +1,8 @@
 use std::fmt::Debug;
 
-use crate::info::BasicBuildInfo;
+use crate::info::{BasicBuildInfo, NULL_BRANCH, NULL_HASH};
 
-use super::query::{OrdPlacement, VersionSearchQuery, WildPlacement};
+use super::query::{VersionSearchQuery, WildPlacement};
 
 type RepoNickname = String;
 
@@ -23,72 +23,282 @@ where
         BInfoMatcher { versions }
     }
 
+    /// Finds the indices of all the `BI`s that are matched by query: [`VersionSearchQuery`].
+    ///
+    /// This is the allocation-light core of [`BInfoMatcher::find_all`]. Every column of the query
+    /// narrows down one list of indices instead of collecting new lists at each stage.
+    /// Build hashes may be given in their short or full form, see [`crate::info::hashes_match`].
+    ///
+    /// Repository nicknames are compared ignoring case and surrounding whitespace.
+    /// Branches also match by their canonical names, so `stable` finds builds from `v4.2-release`,
+    /// see [`crate::info::normalize_branch`].
+    ///
+    /// The [`NULL_BRANCH`] and [`NULL_HASH`] placeholders mean the value is unknown, so querying
+    /// for them is treated the same as [`WildPlacement::Any`].
+    pub fn find_indices(&self, query: &VersionSearchQuery) -> Vec<usize> {
+        let info = |i: usize| -> &BasicBuildInfo { self.versions[i].0.as_ref() };
+
+        let mut indices: Vec<usize> = (0..self.versions.len())
+            .filter(|&i| self.matches_wild(i, query))
+            .collect();
+
+        query
+            .major
+            .retain_indices(&mut indices, |i| &info(i).version().major);
+        query
+            .minor
+            .retain_indices(&mut indices, |i| &info(i).version().minor);
+        query
+            .patch
+            .retain_indices(&mut indices, |i| &info(i).version().patch);
+        query
+            .commit_dt
+            .retain_indices(&mut indices, |i| &info(i).commit_dt);
+
+        indices
+    }
+
+    /// Finds the index of the first `BI` that is matched by query: [`VersionSearchQuery`].
+    ///
+    /// Queries without relative placements (see [`VersionSearchQuery::is_relative`]) are checked one
+    /// build at a time, and stop at the first match. Relative queries like `4.^` need the whole list,
+    /// so they fall back to [`BInfoMatcher::find_indices`] and return its first result.
+    pub fn find_first(&self, query: &VersionSearchQuery) -> Option<usize> {
+        if query.is_relative() {
+            return self.find_indices(query).first().copied();
+        }
+
+        (0..self.versions.len()).find(|&i| {
+            let info = self.versions[i].0.as_ref();
+            let v = info.version();
+
+            self.matches_wild(i, query)
+                && query.major.matches_alone(&v.major)
+                && query.minor.matches_alone(&v.minor)
+                && query.patch.matches_alone(&v.patch)
+                && query.commit_dt.matches_alone(&info.commit_dt)
+        })
+    }
+
+    /// Checks the repository, hash, and branch columns of the query, which don't depend on the other builds.
+    fn matches_wild(&self, i: usize, query: &VersionSearchQuery) -> bool {
+        let (build, repo) = (self.versions[i].0.as_ref(), &self.versions[i].1);
+
+        let r = query.repository.matches_by(|r| nicknames_match(repo, r));
+        let b = match &query.build_hash {
+            WildPlacement::Exact(hash) if hash.eq_ignore_ascii_case(NULL_HASH) => true,
+            hash => hash.matches_by(|hash| build.ver.matches_hash(hash)),
+        };
+        let br = match &query.branch {
+            WildPlacement::Exact(branch) if branch == NULL_BRANCH => true,
+            branch => branch.matches_by(|branch| build.ver.matches_branch(branch)),
+        };
+
+        r && b && br
+    }
+
     /// Finds all the `BI`s that are matched by query: [`VersionSearchQuery`].
     pub fn find_all(&self, query: &VersionSearchQuery) -> Vec<&(BI, RepoNickname)> {
-        let vs = self
-            .versions
-            .iter()
-            .filter_map(|x| {
-                let build: &BasicBuildInfo = x.0.as_ref();
-
-                let r = match query.repository.clone() {
-                    WildPlacement::Any => true,
-                    WildPlacement::Exact(r) => x.1.clone() == r,
-                };
-
-                let b = match query.build_hash.clone() {
-                    WildPlacement::Any => true,
-                    WildPlacement::Exact(hash) => build.ver.build_hash() == hash,
-                };
-                let br = match query.branch.clone() {
-                    WildPlacement::Any => true,
-                    WildPlacement::Exact(branch) => build.ver.branch() == branch,
-                };
-
-                if r && b && br {
-                    Some((build, x))
-                } else {
-                    None
+        self.find_indices(query)
+            .into_iter()
+            .map(|i| &self.versions[i])
+            .collect()
+    }
+
+    /// Finds all the `BI`s that are matched by any of the `queries`, without duplicates and in their original order.
+    ///
+    /// Each query is evaluated against every build on its own, as placements like `^` depend on the
+    /// whole list. Builds are only marked once, so this stays linear in the number of builds per query,
+    /// and stops early once every build has been matched.
+    pub fn find_any(&self, queries: &[VersionSearchQuery]) -> Vec<&(BI, RepoNickname)> {
+        let mut matched = vec![false; self.versions.len()];
+        let mut remaining = self.versions.len();
+
+        for query in queries {
+            if remaining == 0 {
+                break;
+            }
+            for i in self.find_indices(query) {
+                if !matched[i] {
+                    matched[i] = true;
+                    remaining -= 1;
                 }
-            })
-            .collect::<Vec<_>>();
-
-        let vs = match query.major {
-            OrdPlacement::Any => vs,
-            _ => query.major.find(
-                &(vs.iter()
-                    .map(|(v, _)| &v.version().major)
-                    .collect::<Vec<_>>()),
-                |idx| vs[idx],
+            }
+        }
+
+        self.versions
+            .iter()
+            .zip(matched)
+            .filter_map(|(v, m)| m.then_some(v))
+            .collect()
+    }
+}
+
+/// Compares two repository nicknames, ignoring case and surrounding whitespace.
+fn nicknames_match(a: &str, b: &str) -> bool {
+    a.trim()
+        .chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.trim().chars().flat_map(char::to_lowercase))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        info::VerboseVersion,
+        search::{VersionSearchQuery, WildPlacement},
+        BasicBuildInfo,
+    };
+
+    use super::BInfoMatcher;
+
+    #[test]
+    fn test_short_and_full_hashes() {
+        let full = "ddc9f92777cd4f5e0b7a1c2d3e4f5a6b7c8d9e0f";
+        let builds = vec![
+            (
+                BasicBuildInfo {
+                    ver: VerboseVersion::new(4, 3, 0, None, Some("main"), Some(full)),
+                    ..Default::default()
+                },
+                "daily".to_string(),
             ),
-        };
-        let vs = match query.minor {
-            OrdPlacement::Any => vs,
-            _ => query.minor.find(
-                &(vs.iter()
-                    .map(|(v, _)| &v.version().minor)
-                    .collect::<Vec<_>>()),
-                |idx| vs[idx],
+            (
+                BasicBuildInfo {
+                    ver: VerboseVersion::new(4, 2, 0, None, Some("main"), Some("ddc9f927")),
+                    ..Default::default()
+                },
+                "daily".to_string(),
             ),
-        };
-        let vs = match query.patch {
-            OrdPlacement::Any => vs,
-            _ => query.patch.find(
-                &(vs.iter()
-                    .map(|(v, _)| &v.version().patch)
-                    .collect::<Vec<_>>()),
-                |idx| vs[idx],
+            (
+                BasicBuildInfo {
+                    ver: VerboseVersion::new(4, 1, 0, None, Some("main"), Some("ddc9aaaa")),
+                    ..Default::default()
+                },
+                "daily".to_string(),
             ),
+        ];
+        let matcher = BInfoMatcher::new(&builds);
+        let query = |hash: &str| VersionSearchQuery {
+            build_hash: WildPlacement::Exact(hash.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq![matcher.find_indices(&query("ddc9f92")), vec![0, 1]];
+        assert_eq![matcher.find_indices(&query(full)), vec![0, 1]];
+        assert_eq![matcher.find_indices(&query("ddc9f92777cd")), vec![0, 1]];
+        assert_eq![matcher.find_indices(&query("ddc9f9270000")), vec![1]];
+        assert![matcher.find_indices(&query("ddc9")).is_empty()];
+    }
+
+    #[test]
+    fn test_find_any() {
+        let build = |minor: u64, branch: &str| {
+            (
+                BasicBuildInfo {
+                    ver: VerboseVersion::new(4, minor, 0, None, Some(branch), None),
+                    ..Default::default()
+                },
+                "daily".to_string(),
+            )
         };
+        let builds = vec![build(1, "lts"), build(2, "stable"), build(3, "lts")];
+        let matcher = BInfoMatcher::new(&builds);
+
+        let queries = [
+            VersionSearchQuery::try_from("4.^-stable").unwrap(),
+            VersionSearchQuery::try_from("4.^-lts").unwrap(),
+            VersionSearchQuery::try_from("4.3").unwrap(),
+        ];
+        let found: Vec<_> = matcher
+            .find_any(&queries)
+            .into_iter()
+            .map(|(b, _)| b.version().minor)
+            .collect();
+        assert_eq![found, [2, 3]];
+        assert![matcher.find_any(&[]).is_empty()];
+    }
+
+    #[test]
+    fn test_negated_queries() {
+        let builds = vec![
+            (BasicBuildInfo::default(), "daily".to_string()),
+            (
+                BasicBuildInfo {
+                    ver: VerboseVersion::new(4, 2, 0, None, Some("experimental"), None),
+                    ..Default::default()
+                },
+                "experimental".to_string(),
+            ),
+        ];
+        let matcher = BInfoMatcher::new(&builds);
+        let find = |q: &str| matcher.find_indices(&VersionSearchQuery::try_from(q).unwrap());
+
+        assert_eq![find("*.*-!experimental"), vec![0]];
+        assert_eq![find("!daily/*.*"), vec![1]];
+        assert_eq![find("!experimental/*.*-!experimental"), vec![0]];
+        assert![VersionSearchQuery::try_from("!*/*.*").is_err()];
+    }
 
-        let vs = match query.commit_dt {
-            OrdPlacement::Any => vs,
-            _ => query.commit_dt.find(
-                &(vs.iter().map(|(v, _)| &v.commit_dt).collect::<Vec<_>>()),
-                |idx| vs[idx],
+    #[test]
+    fn test_sentinel_queries() {
+        let builds = vec![
+            (
+                BasicBuildInfo {
+                    ver: VerboseVersion::new(4, 3, 0, None, Some("main"), Some("ddc9f92777cd")),
+                    ..Default::default()
+                },
+                "daily".to_string(),
             ),
+            (BasicBuildInfo::default(), "daily".to_string()),
+        ];
+        let matcher = BInfoMatcher::new(&builds);
+
+        let query = VersionSearchQuery {
+            branch: WildPlacement::Exact("null".to_string()),
+            ..Default::default()
+        };
+        assert_eq![matcher.find_indices(&query), vec![0, 1]];
+
+        let query = VersionSearchQuery {
+            build_hash: WildPlacement::Exact("ffffffff".to_string()),
+            ..Default::default()
         };
+        assert_eq![matcher.find_indices(&query), vec![0, 1]];
 
-        vs.into_iter().map(|(_, x)| x).collect()
+        // A shortened sentinel is a real query, which unknown hashes never match
+        let query = VersionSearchQuery {
+            build_hash: WildPlacement::Exact("fffffff".to_string()),
+            ..Default::default()
+        };
+        assert![matcher.find_indices(&query).is_empty()];
+    }
+
+    #[test]
+    fn test_mixed_case_repos() {
+        let builds = vec![
+            (BasicBuildInfo::default(), "daily".to_string()),
+            (BasicBuildInfo::default(), " Experimental ".to_string()),
+        ];
+        let matcher = BInfoMatcher::new(&builds);
+        let find = |q: &str| matcher.find_indices(&VersionSearchQuery::try_from(q).unwrap());
+
+        assert_eq![find("Daily/*.*"), vec![0]];
+        assert_eq![find("DAILY/*.*"), vec![0]];
+        assert_eq![find("experimental/*.*"), vec![1]];
+        assert_eq![find("!daily/*.*"), vec![1]];
+        assert_eq![
+            VersionSearchQuery::try_from("Daily/4.3")
+                .unwrap()
+                .repository
+                .to_string(),
+            "daily"
+        ];
+
+        let query = VersionSearchQuery {
+            repository: WildPlacement::Exact("  eXperimental".to_string()),
+            ..Default::default()
+        };
+        assert_eq![matcher.find_indices(&query), vec![1]];
     }
 }